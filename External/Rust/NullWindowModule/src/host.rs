@@ -0,0 +1,631 @@
+//! Safe Rust host-side wrapper for driving a dng ABI v1 or v2 module.
+//!
+//! The wrapper builds a `dng_host_api_v1` backed by the Rust global allocator
+//! and the `log` crate, fetches the module table through the module's
+//! `dngModuleGetApi_v1` or `dngModuleGetApi_v2` entry point and calls
+//! `shutdown` exactly once. Calls a v1 module lacks return
+//! `DngStatus::Unsupported`.
+
+use core::ffi::{c_char, c_void};
+use core::mem::{size_of, MaybeUninit};
+use core::ptr;
+use std::alloc::{alloc, dealloc, Layout};
+
+use crate::*;
+
+/// Signature of the `dngModuleGetApi_v1` entry point (e.g. a libloading symbol).
+pub type GetApiV1Fn = unsafe extern "C" fn(*const dng_host_api_v1, *mut dng_module_api_v1) -> dng_status_v1;
+
+/// Signature of the `dngModuleGetApi_v2` entry point.
+pub type GetApiV2Fn = unsafe extern "C" fn(*const dng_host_api_v1, *mut dng_module_api_v2) -> dng_status_v1;
+
+/// A module entry point, tagged with the ABI version of the table it fills.
+#[derive(Copy, Clone)]
+pub enum GetApiFn {
+    V1(GetApiV1Fn),
+    V2(GetApiV2Fn),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DngStatus {
+    Ok,
+    Fail,
+    InvalidArg,
+    OutOfMemory,
+    Unsupported,
+    Empty,
+    NotFound,
+    InvalidUtf8,
+    Unknown(dng_status_v1),
+}
+
+impl From<dng_status_v1> for DngStatus {
+    fn from(status: dng_status_v1) -> DngStatus {
+        match status {
+            DNG_STATUS_OK => DngStatus::Ok,
+            DNG_STATUS_FAIL => DngStatus::Fail,
+            DNG_STATUS_INVALID_ARG => DngStatus::InvalidArg,
+            DNG_STATUS_OUT_OF_MEMORY => DngStatus::OutOfMemory,
+            DNG_STATUS_UNSUPPORTED => DngStatus::Unsupported,
+            DNG_STATUS_EMPTY => DngStatus::Empty,
+            DNG_STATUS_NOT_FOUND => DngStatus::NotFound,
+            DNG_STATUS_INVALID_UTF8 => DngStatus::InvalidUtf8,
+            other => DngStatus::Unknown(other),
+        }
+    }
+}
+
+fn check(status: dng_status_v1) -> Result<(), DngStatus> {
+    match DngStatus::from(status) {
+        DngStatus::Ok => Ok(()),
+        err => Err(err),
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WindowHandle(dng_window_handle_v1);
+
+impl WindowHandle {
+    pub fn raw(self) -> dng_window_handle_v1 {
+        self.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PointerPhase {
+    Down,
+    Move,
+    Up,
+}
+
+/// Safe view of a `dng_window_event_v1`.
+///
+/// Event types this wrapper does not know yet are kept as `Unknown` with the
+/// raw payload, so `to_raw` hands them back unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Resize { window: WindowHandle, size: WindowSize },
+    Close { window: WindowHandle },
+    Focus { window: WindowHandle, focused: bool },
+    Ipc { window: WindowHandle, source: WindowHandle, kind: u32, data: Vec<u8> },
+    QuitRequested,
+    Touch { window: WindowHandle, phase: PointerPhase, x: i32, y: i32, touch_id: u32 },
+    Mouse { window: WindowHandle, phase: PointerPhase, x: i32, y: i32 },
+    Unknown { event_type: dng_window_event_type_v1, window: WindowHandle, payload: [dng_u64; 4] },
+}
+
+// Only called once the event type is known to match, so the accessor cannot fail.
+fn read_payload<T>(
+    raw: &dng_window_event_v1,
+    get: extern "C" fn(*const dng_window_event_v1, *mut T) -> dng_status_v1,
+) -> T {
+    let mut out = MaybeUninit::<T>::uninit();
+    let status = get(raw, out.as_mut_ptr());
+    assert_eq!(status, DNG_STATUS_OK, "event accessor rejected a matching event type");
+    unsafe { out.assume_init() }
+}
+
+impl From<dng_window_event_v1> for Event {
+    fn from(raw: dng_window_event_v1) -> Event {
+        let window = WindowHandle(raw.handle);
+        let phase = match raw.event_type {
+            DNG_EVENT_TOUCH_DOWN | DNG_EVENT_MOUSE_DOWN => Some(PointerPhase::Down),
+            DNG_EVENT_TOUCH_MOVE | DNG_EVENT_MOUSE_MOVE => Some(PointerPhase::Move),
+            DNG_EVENT_TOUCH_UP | DNG_EVENT_MOUSE_UP => Some(PointerPhase::Up),
+            _ => None,
+        };
+        match raw.event_type {
+            DNG_EVENT_RESIZE => {
+                let size = read_payload(&raw, dngEventGetResize);
+                return Event::Resize { window, size: WindowSize { width: size.width, height: size.height } };
+            }
+            DNG_EVENT_CLOSE => return Event::Close { window },
+            DNG_EVENT_FOCUS => {
+                let focus = read_payload(&raw, dngEventGetFocus);
+                return Event::Focus { window, focused: focus.focused != DNG_BOOL_FALSE };
+            }
+            DNG_EVENT_IPC => {
+                let ipc = read_payload(&raw, dngEventGetIpc);
+                // A size past the inline buffer is malformed; keep it raw rather than truncate.
+                if let Some(data) = ipc.data.get(..ipc.size as usize) {
+                    return Event::Ipc { window, source: WindowHandle(ipc.source), kind: ipc.kind, data: data.to_vec() };
+                }
+            }
+            DNG_EVENT_QUIT_REQUESTED => return Event::QuitRequested,
+            DNG_EVENT_TOUCH_DOWN | DNG_EVENT_TOUCH_MOVE | DNG_EVENT_TOUCH_UP => {
+                let pointer = read_payload(&raw, dngEventGetPointer);
+                return Event::Touch { window, phase: phase.unwrap(), x: pointer.x, y: pointer.y, touch_id: pointer.touch_id };
+            }
+            DNG_EVENT_MOUSE_DOWN | DNG_EVENT_MOUSE_MOVE | DNG_EVENT_MOUSE_UP => {
+                let pointer = read_payload(&raw, dngEventGetPointer);
+                return Event::Mouse { window, phase: phase.unwrap(), x: pointer.x, y: pointer.y };
+            }
+            _ => {}
+        }
+        Event::Unknown { event_type: raw.event_type, window, payload: unsafe { raw.payload.reserved } }
+    }
+}
+
+impl Event {
+    /// IPC data longer than `DNG_IPC_PAYLOAD_MAX` bytes is truncated.
+    pub fn to_raw(&self) -> dng_window_event_v1 {
+        let mut payload = dng_window_event_payload_v1 { reserved: [0; 4] };
+        let (event_type, handle) = match self {
+            Event::Resize { window, size } => {
+                payload.size = dng_window_size_v1 { width: size.width, height: size.height };
+                (DNG_EVENT_RESIZE, window.0)
+            }
+            Event::Close { window } => (DNG_EVENT_CLOSE, window.0),
+            Event::Focus { window, focused } => {
+                payload.focus = dng_window_focus_v1 { focused: if *focused { DNG_BOOL_TRUE } else { DNG_BOOL_FALSE } };
+                (DNG_EVENT_FOCUS, window.0)
+            }
+            Event::Ipc { window, source, kind, data } => {
+                let mut ipc = dng_window_ipc_v1 { source: source.0, kind: *kind, size: 0, data: [0; DNG_IPC_PAYLOAD_MAX as usize] };
+                let len = data.len().min(ipc.data.len());
+                ipc.data[..len].copy_from_slice(&data[..len]);
+                ipc.size = len as dng_u32;
+                payload.ipc = ipc;
+                (DNG_EVENT_IPC, window.0)
+            }
+            Event::QuitRequested => (DNG_EVENT_QUIT_REQUESTED, 0),
+            Event::Touch { window, phase, x, y, touch_id } => {
+                payload.pointer = dng_window_pointer_v1 { x: *x, y: *y, touch_id: *touch_id };
+                let event_type = match phase {
+                    PointerPhase::Down => DNG_EVENT_TOUCH_DOWN,
+                    PointerPhase::Move => DNG_EVENT_TOUCH_MOVE,
+                    PointerPhase::Up => DNG_EVENT_TOUCH_UP,
+                };
+                (event_type, window.0)
+            }
+            Event::Mouse { window, phase, x, y } => {
+                payload.pointer = dng_window_pointer_v1 { x: *x, y: *y, touch_id: 0 };
+                let event_type = match phase {
+                    PointerPhase::Down => DNG_EVENT_MOUSE_DOWN,
+                    PointerPhase::Move => DNG_EVENT_MOUSE_MOVE,
+                    PointerPhase::Up => DNG_EVENT_MOUSE_UP,
+                };
+                (event_type, window.0)
+            }
+            Event::Unknown { event_type, window, payload: raw } => {
+                payload.reserved = *raw;
+                (*event_type, window.0)
+            }
+        };
+        dng_window_event_v1 { event_type, handle, timestamp: 0, payload }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct WindowDesc<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub title: &'a str,
+    pub flags: u32,
+}
+
+fn str_view(s: &str) -> Result<dng_str_view_v1, DngStatus> {
+    let size = dng_u32::try_from(s.len()).map_err(|_| DngStatus::InvalidArg)?;
+    Ok(dng_str_view_v1 { data: s.as_ptr() as *const c_char, size })
+}
+
+extern "C" fn host_alloc(_user: *mut c_void, size: dng_u64, align: dng_u64) -> *mut c_void {
+    match Layout::from_size_align(size as usize, align as usize) {
+        Ok(layout) if layout.size() > 0 => unsafe { alloc(layout) as *mut c_void },
+        _ => ptr::null_mut(),
+    }
+}
+
+extern "C" fn host_free(_user: *mut c_void, mem: *mut c_void, size: dng_u64, align: dng_u64) {
+    if mem.is_null() {
+        return;
+    }
+    if let Ok(layout) = Layout::from_size_align(size as usize, align as usize) {
+        unsafe { dealloc(mem as *mut u8, layout) };
+    }
+}
+
+extern "C" fn host_log(_user: *mut c_void, level: dng_u32, msg: dng_str_view_v1) {
+    let bytes = if msg.size == 0 || msg.data.is_null() {
+        &[][..]
+    } else {
+        unsafe { core::slice::from_raw_parts(msg.data as *const u8, msg.size as usize) }
+    };
+    let level = match level {
+        DNG_LOG_LEVEL_TRACE => log::Level::Trace,
+        DNG_LOG_LEVEL_DEBUG => log::Level::Debug,
+        DNG_LOG_LEVEL_INFO => log::Level::Info,
+        DNG_LOG_LEVEL_WARN => log::Level::Warn,
+        _ => log::Level::Error,
+    };
+    log::log!(target: "dng_module", level, "{}", String::from_utf8_lossy(bytes));
+}
+
+// Shuts down a module whose table this wrapper cannot use.
+unsafe fn reject(
+    shutdown: Option<extern "C" fn(*mut c_void, *const dng_host_api_v1) -> dng_status_v1>,
+    ctx: *mut c_void,
+    host: &dng_host_api_v1,
+) -> DngStatus {
+    if let Some(shutdown) = shutdown {
+        shutdown(ctx, host);
+    }
+    DngStatus::Unsupported
+}
+
+unsafe fn fetch_v2(get_api: GetApiV2Fn, host: &dng_host_api_v1) -> Result<dng_module_api_v2, DngStatus> {
+    // Declare the table sizes this wrapper was built against; the module fills no more.
+    let mut api = MaybeUninit::<dng_module_api_v2>::zeroed();
+    let out = api.as_mut_ptr();
+    (*out).header = dng_abi_header_v1 { struct_size: size_of::<dng_module_api_v2>() as dng_u32, abi_version: DNG_ABI_VERSION_V2 };
+    (*out).window.header = dng_abi_header_v1 { struct_size: size_of::<dng_window_api_v2>() as dng_u32, abi_version: DNG_ABI_VERSION_V2 };
+    (*out).audio.header = dng_abi_header_v1 { struct_size: size_of::<dng_audio_api_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 };
+    check(get_api(host, out))?;
+    let api = api.assume_init();
+    if api.header.abi_version != DNG_ABI_VERSION_V2 || api.window.header.abi_version != DNG_ABI_VERSION_V2 {
+        return Err(reject(api.shutdown, api.window.ctx, host));
+    }
+    Ok(api)
+}
+
+// Widens a v1 table into the v2 layout; the v2-only callbacks stay None.
+unsafe fn fetch_v1(get_api: GetApiV1Fn, host: &dng_host_api_v1) -> Result<dng_module_api_v2, DngStatus> {
+    let mut api = MaybeUninit::<dng_module_api_v1>::zeroed();
+    let out = api.as_mut_ptr();
+    (*out).header = dng_abi_header_v1 { struct_size: size_of::<dng_module_api_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 };
+    (*out).window.header = dng_abi_header_v1 { struct_size: size_of::<dng_window_api_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 };
+    check(get_api(host, out))?;
+    let v1 = api.assume_init();
+    if v1.header.abi_version != DNG_ABI_VERSION_V1 || v1.window.header.abi_version != DNG_ABI_VERSION_V1 {
+        return Err(reject(v1.shutdown, v1.window.ctx, host));
+    }
+    let mut wide = MaybeUninit::<dng_module_api_v2>::zeroed().assume_init();
+    wide.header = v1.header;
+    wide.module_name = v1.module_name;
+    wide.module_version_major = v1.module_version_major;
+    wide.module_version_minor = v1.module_version_minor;
+    wide.module_version_patch = v1.module_version_patch;
+    wide.window.header = v1.window.header;
+    wide.window.ctx = v1.window.ctx;
+    wide.window.create = v1.window.create;
+    wide.window.destroy = v1.window.destroy;
+    wide.window.poll = v1.window.poll;
+    wide.window.get_size = v1.window.get_size;
+    wide.window.set_title = v1.window.set_title;
+    wide.shutdown = v1.shutdown;
+    Ok(wide)
+}
+
+/// A loaded module instance. Dropping it shuts the module down.
+pub struct Module {
+    // Boxed so the address handed to the module stays stable when Module moves.
+    host: Box<dng_host_api_v1>,
+    api: dng_module_api_v2,
+    shut_down: bool,
+}
+
+impl Module {
+    /// Builds the host services and fetches the module table through `get_api`.
+    ///
+    /// # Safety
+    /// `get_api` must be a `dngModuleGetApi_v1` or `dngModuleGetApi_v2`
+    /// implementation matching its tag and honoring the ABI contract, and the
+    /// library providing it must outlive the Module.
+    pub unsafe fn load(get_api: GetApiFn) -> Result<Module, DngStatus> {
+        let host = Box::new(dng_host_api_v1 {
+            header: dng_abi_header_v1 { struct_size: size_of::<dng_host_api_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 },
+            user: ptr::null_mut(),
+            log: Some(host_log),
+            alloc: Some(host_alloc),
+            free: Some(host_free),
+        });
+        let api = match get_api {
+            GetApiFn::V1(get_api) => fetch_v1(get_api, &host)?,
+            GetApiFn::V2(get_api) => fetch_v2(get_api, &host)?,
+        };
+        Ok(Module { host, api, shut_down: false })
+    }
+
+    /// Loads the null window module linked into this crate.
+    pub fn load_null_window() -> Result<Module, DngStatus> {
+        unsafe { Module::load(GetApiFn::V2(dngModuleGetApi_v2)) }
+    }
+
+    pub fn name(&self) -> String {
+        let name = self.api.module_name;
+        if name.size == 0 || name.data.is_null() {
+            return String::new();
+        }
+        let bytes = unsafe { core::slice::from_raw_parts(name.data as *const u8, name.size as usize) };
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+
+    pub fn version(&self) -> (u32, u32, u32) {
+        (self.api.module_version_major, self.api.module_version_minor, self.api.module_version_patch)
+    }
+
+    pub fn create_window(&self, desc: WindowDesc) -> Result<WindowHandle, DngStatus> {
+        let create = self.api.window.create.ok_or(DngStatus::Unsupported)?;
+        let raw_desc = dng_window_desc_v1 { width: desc.width, height: desc.height, title: str_view(desc.title)?, flags: desc.flags };
+        let mut handle: dng_window_handle_v1 = 0;
+        check(create(self.api.window.ctx, &raw_desc, &mut handle))?;
+        Ok(WindowHandle(handle))
+    }
+
+    pub fn destroy_window(&self, handle: WindowHandle) -> Result<(), DngStatus> {
+        let destroy = self.api.window.destroy.ok_or(DngStatus::Unsupported)?;
+        check(destroy(self.api.window.ctx, handle.0))
+    }
+
+    pub fn poll(&self) -> Result<(), DngStatus> {
+        let poll = self.api.window.poll.ok_or(DngStatus::Unsupported)?;
+        check(poll(self.api.window.ctx))
+    }
+
+    pub fn get_size(&self, handle: WindowHandle) -> Result<WindowSize, DngStatus> {
+        let get_size = self.api.window.get_size.ok_or(DngStatus::Unsupported)?;
+        let mut size = dng_window_size_v1 { width: 0, height: 0 };
+        check(get_size(self.api.window.ctx, handle.0, &mut size))?;
+        Ok(WindowSize { width: size.width, height: size.height })
+    }
+
+    pub fn set_title(&self, handle: WindowHandle, title: &str) -> Result<(), DngStatus> {
+        let set_title = self.api.window.set_title.ok_or(DngStatus::Unsupported)?;
+        check(set_title(self.api.window.ctx, handle.0, str_view(title)?))
+    }
+
+    /// Pops the next pending event; `Ok(None)` once the queue is empty.
+    pub fn poll_event(&self) -> Result<Option<Event>, DngStatus> {
+        Ok(self.poll_timed_event()?.map(|(event, _)| event))
+    }
+
+    /// Like `poll_event`, also returning the event's timestamp in engine ticks.
+    pub fn poll_timed_event(&self) -> Result<Option<(Event, u64)>, DngStatus> {
+        let poll_event = self.api.window.poll_event.ok_or(DngStatus::Unsupported)?;
+        let mut raw = MaybeUninit::<dng_window_event_v1>::zeroed();
+        match check(poll_event(self.api.window.ctx, raw.as_mut_ptr())) {
+            Ok(()) => {
+                let raw = unsafe { raw.assume_init() };
+                Ok(Some((Event::from(raw), raw.timestamp)))
+            }
+            Err(DngStatus::Empty) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Asks the host to save and quit, as platform glue would on logoff.
+    pub fn request_quit(&self) -> Result<(), DngStatus> {
+        let request_quit = self.api.window.request_quit.ok_or(DngStatus::Unsupported)?;
+        check(request_quit(self.api.window.ctx))
+    }
+
+    /// True while a quit request is pending; modules without quit support never request one.
+    pub fn quit_requested(&self) -> Result<bool, DngStatus> {
+        let get_quit_requested = match self.api.window.get_quit_requested {
+            Some(f) => f,
+            None => return Ok(false),
+        };
+        let mut requested = DNG_BOOL_FALSE;
+        check(get_quit_requested(self.api.window.ctx, &mut requested))?;
+        Ok(requested != DNG_BOOL_FALSE)
+    }
+
+    pub fn cancel_quit(&self) -> Result<(), DngStatus> {
+        let cancel_quit = self.api.window.cancel_quit.ok_or(DngStatus::Unsupported)?;
+        check(cancel_quit(self.api.window.ctx))
+    }
+
+    /// Shuts the module down and reports the module's status. Drop does the
+    /// same silently.
+    pub fn shutdown(mut self) -> Result<(), DngStatus> {
+        self.shutdown_once()
+    }
+
+    fn shutdown_once(&mut self) -> Result<(), DngStatus> {
+        if self.shut_down {
+            return Ok(());
+        }
+        self.shut_down = true;
+        match self.api.shutdown {
+            Some(shutdown) => check(shutdown(self.api.window.ctx, &*self.host)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Module {
+    fn drop(&mut self) {
+        let _ = self.shutdown_once();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn status_codes_map_to_enum() {
+        assert_eq!(DngStatus::from(DNG_STATUS_OK), DngStatus::Ok);
+        assert_eq!(DngStatus::from(DNG_STATUS_FAIL), DngStatus::Fail);
+        assert_eq!(DngStatus::from(DNG_STATUS_INVALID_ARG), DngStatus::InvalidArg);
+        assert_eq!(DngStatus::from(DNG_STATUS_OUT_OF_MEMORY), DngStatus::OutOfMemory);
+        assert_eq!(DngStatus::from(DNG_STATUS_UNSUPPORTED), DngStatus::Unsupported);
+        assert_eq!(DngStatus::from(DNG_STATUS_EMPTY), DngStatus::Empty);
+        assert_eq!(DngStatus::from(DNG_STATUS_NOT_FOUND), DngStatus::NotFound);
+        assert_eq!(DngStatus::from(DNG_STATUS_INVALID_UTF8), DngStatus::InvalidUtf8);
+        assert_eq!(DngStatus::from(1234), DngStatus::Unknown(1234));
+    }
+
+    #[test]
+    fn main_loop_stops_on_quit_request() {
+        let module = Module::load_null_window().expect("null window module loads");
+        let main = module.create_window(WindowDesc { width: 640, height: 480, title: "Main", flags: 0 }).unwrap();
+        let mut frames = 0;
+        while !module.quit_requested().unwrap() {
+            module.poll().unwrap();
+            frames += 1;
+            if frames == 3 {
+                module.request_quit().unwrap();
+            }
+        }
+        assert_eq!(frames, 3);
+
+        assert_eq!(module.cancel_quit(), Ok(()));
+        assert_eq!(module.quit_requested(), Ok(false));
+        assert_eq!(module.request_quit(), Ok(()));
+        assert_eq!(module.quit_requested(), Ok(true));
+        assert_eq!(module.destroy_window(main), Ok(()));
+        assert_eq!(module.shutdown(), Ok(()));
+    }
+
+    #[test]
+    fn drives_null_window_module_end_to_end() {
+        let module = Module::load_null_window().expect("null window module loads");
+        assert_eq!(module.name(), "RustNullWindow");
+        assert_eq!(module.version(), (1, 0, 0));
+
+        let main = module.create_window(WindowDesc { width: 1280, height: 720, title: "Main", flags: 0 }).unwrap();
+        let tool = module.create_window(WindowDesc { width: 320, height: 240, title: "Tools", flags: 0 }).unwrap();
+        assert_eq!(module.get_size(main), Ok(WindowSize { width: 1280, height: 720 }));
+        assert_eq!(module.get_size(tool), Ok(WindowSize { width: 320, height: 240 }));
+        assert_eq!(module.set_title(main, "Main - level.dng"), Ok(()));
+        assert_eq!(module.poll(), Ok(()));
+
+        assert_eq!(module.destroy_window(main), Ok(()));
+        assert_eq!(module.destroy_window(main), Err(DngStatus::InvalidArg));
+        assert_eq!(module.get_size(main), Err(DngStatus::InvalidArg));
+        assert_eq!(module.set_title(main, "stale"), Err(DngStatus::InvalidArg));
+        assert_eq!(
+            module.create_window(WindowDesc { width: 1, height: 1, title: "", flags: 1 << 31 }),
+            Err(DngStatus::Unsupported)
+        );
+        assert_eq!(module.shutdown(), Ok(()));
+    }
+
+    #[test]
+    fn drop_shuts_down_surviving_windows() {
+        let module = Module::load_null_window().unwrap();
+        module.create_window(WindowDesc { width: 640, height: 480, title: "Leaked", flags: 0 }).unwrap();
+        drop(module);
+    }
+
+    #[test]
+    fn loads_v1_modules_without_the_v2_calls() {
+        let module = unsafe { Module::load(GetApiFn::V1(dngModuleGetApi_v1)) }.expect("v1 module loads");
+        assert_eq!(module.name(), "RustNullWindow");
+        let main = module.create_window(WindowDesc { width: 640, height: 480, title: "Main", flags: 0 }).unwrap();
+        assert_eq!(module.get_size(main), Ok(WindowSize { width: 640, height: 480 }));
+        assert_eq!(module.set_title(main, "Main - v1"), Ok(()));
+        assert_eq!(module.poll(), Ok(()));
+
+        assert_eq!(module.poll_event(), Err(DngStatus::Unsupported));
+        assert_eq!(module.request_quit(), Err(DngStatus::Unsupported));
+        assert_eq!(module.cancel_quit(), Err(DngStatus::Unsupported));
+        assert_eq!(module.quit_requested(), Ok(false));
+        assert_eq!(module.destroy_window(main), Ok(()));
+        assert_eq!(module.shutdown(), Ok(()));
+    }
+
+    static FAKE_SHUTDOWNS: AtomicUsize = AtomicUsize::new(0);
+
+    extern "C" fn fake_shutdown(_ctx: *mut c_void, _host: *const dng_host_api_v1) -> dng_status_v1 {
+        FAKE_SHUTDOWNS.fetch_add(1, Ordering::SeqCst);
+        DNG_STATUS_OK
+    }
+
+    extern "C" fn fake_get_api(host: *const dng_host_api_v1, out_api: *mut dng_module_api_v2) -> dng_status_v1 {
+        let status = dngModuleGetApi_v2(host, out_api);
+        if status == DNG_STATUS_OK {
+            // Release the real context right away so only the fake shutdown remains.
+            unsafe {
+                let api = &mut *out_api;
+                (api.shutdown.unwrap())(api.window.ctx, host);
+                api.window.ctx = ptr::null_mut();
+                api.shutdown = Some(fake_shutdown);
+            }
+        }
+        status
+    }
+
+    #[test]
+    fn shutdown_runs_exactly_once() {
+        let module = unsafe { Module::load(GetApiFn::V2(fake_get_api)) }.unwrap();
+        assert_eq!(module.shutdown(), Ok(()));
+        assert_eq!(FAKE_SHUTDOWNS.load(Ordering::SeqCst), 1);
+
+        let module = unsafe { Module::load(GetApiFn::V2(fake_get_api)) }.unwrap();
+        drop(module);
+        assert_eq!(FAKE_SHUTDOWNS.load(Ordering::SeqCst), 2);
+    }
+
+    fn same_raw(a: &dng_window_event_v1, b: &dng_window_event_v1) -> bool {
+        a.event_type == b.event_type && a.handle == b.handle && unsafe { a.payload.reserved == b.payload.reserved }
+    }
+
+    #[test]
+    fn events_round_trip_through_raw() {
+        let window = WindowHandle(dng_make_handle(2, 5));
+        let mut events = vec![
+            Event::Resize { window, size: WindowSize { width: 800, height: 600 } },
+            Event::Close { window },
+            Event::Focus { window, focused: true },
+            Event::Focus { window, focused: false },
+            Event::Ipc { window, source: WindowHandle(dng_make_handle(3, 1)), kind: 7, data: b"ping".to_vec() },
+            Event::QuitRequested,
+        ];
+        for phase in [PointerPhase::Down, PointerPhase::Move, PointerPhase::Up] {
+            events.push(Event::Touch { window, phase, x: -4, y: 9, touch_id: 3 });
+            events.push(Event::Mouse { window, phase, x: 12, y: -1 });
+        }
+        for event in events {
+            let raw = event.to_raw();
+            assert_eq!(Event::from(raw), event);
+            assert!(same_raw(&Event::from(raw).to_raw(), &raw));
+        }
+    }
+
+    #[test]
+    fn unknown_event_types_round_trip_without_loss() {
+        let raw = dng_window_event_v1 {
+            event_type: 99,
+            handle: dng_make_handle(1, 2),
+            timestamp: 0,
+            payload: dng_window_event_payload_v1 { reserved: [0x0123_4567_89ab_cdef, 1, u64::MAX, 42] },
+        };
+        let event = Event::from(raw);
+        assert_eq!(
+            event,
+            Event::Unknown { event_type: 99, window: WindowHandle(raw.handle), payload: [0x0123_4567_89ab_cdef, 1, u64::MAX, 42] }
+        );
+        assert!(same_raw(&event.to_raw(), &raw));
+
+        // An IPC size past the inline buffer is kept raw instead of being truncated.
+        let mut bad_ipc = raw;
+        bad_ipc.event_type = DNG_EVENT_IPC;
+        bad_ipc.payload.ipc.size = DNG_IPC_PAYLOAD_MAX + 1;
+        assert!(matches!(Event::from(bad_ipc), Event::Unknown { event_type: DNG_EVENT_IPC, .. }));
+        assert!(same_raw(&Event::from(bad_ipc).to_raw(), &bad_ipc));
+    }
+
+    #[test]
+    fn poll_event_yields_typed_events() {
+        let module = Module::load_null_window().unwrap();
+        let main = module.create_window(WindowDesc { width: 640, height: 480, title: "Main", flags: 0 }).unwrap();
+        assert_eq!(module.poll_event(), Ok(None));
+
+        let resize = Event::Resize { window: main, size: WindowSize { width: 320, height: 200 } };
+        assert_eq!(dngNullWindowInjectEvent_v1(module.api.window.ctx, &resize.to_raw()), DNG_STATUS_OK);
+        module.request_quit().unwrap();
+        assert_eq!(module.poll_event(), Ok(Some(resize)));
+        assert_eq!(module.poll_event(), Ok(Some(Event::QuitRequested)));
+        assert_eq!(module.poll_event(), Ok(None));
+        assert_eq!(module.shutdown(), Ok(()));
+    }
+}
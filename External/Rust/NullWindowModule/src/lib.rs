@@ -1,13 +1,21 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(dead_code)]
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
 
 use core::ffi::c_char;
 use core::ffi::c_void;
+use core::fmt;
 use core::mem::{align_of, size_of};
-use core::panic::UnwindSafe;
+use core::panic::{RefUnwindSafe, UnwindSafe};
 use core::ptr;
+use std::alloc::Layout;
 use std::panic::catch_unwind;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+pub mod host;
 
 pub type dng_u8 = u8;
 pub type dng_u32 = u32;
@@ -19,12 +27,46 @@ pub const DNG_STATUS_FAIL: dng_status_v1 = 1;
 pub const DNG_STATUS_INVALID_ARG: dng_status_v1 = 2;
 pub const DNG_STATUS_OUT_OF_MEMORY: dng_status_v1 = 3;
 pub const DNG_STATUS_UNSUPPORTED: dng_status_v1 = 4;
+pub const DNG_STATUS_EMPTY: dng_status_v1 = 5;
+pub const DNG_STATUS_NOT_FOUND: dng_status_v1 = 6;
+pub const DNG_STATUS_INVALID_UTF8: dng_status_v1 = 7;
 
 pub const DNG_ABI_VERSION_V1: dng_u32 = 1;
+pub const DNG_ABI_VERSION_V2: dng_u32 = 2;
+
+// Levels passed to dng_host_api_v1.log by this module.
+pub const DNG_LOG_LEVEL_TRACE: dng_u32 = 0;
+pub const DNG_LOG_LEVEL_DEBUG: dng_u32 = 1;
+pub const DNG_LOG_LEVEL_INFO: dng_u32 = 2;
+pub const DNG_LOG_LEVEL_WARN: dng_u32 = 3;
+pub const DNG_LOG_LEVEL_ERROR: dng_u32 = 4;
+
+// Window handles pack a 32-bit slot index (low bits) with a 32-bit generation
+// (high bits). Generations start at 1, so a valid handle is never 0.
+#[inline]
+pub const fn dng_handle_slot(handle: dng_window_handle_v1) -> dng_u32 {
+    handle as dng_u32
+}
+
+#[inline]
+pub const fn dng_handle_generation(handle: dng_window_handle_v1) -> dng_u32 {
+    (handle >> 32) as dng_u32
+}
+
+#[inline]
+pub const fn dng_make_handle(slot: dng_u32, generation: dng_u32) -> dng_window_handle_v1 {
+    ((generation as dng_window_handle_v1) << 32) | slot as dng_window_handle_v1
+}
+
+pub const DNG_API_FAMILY_WINDOW: dng_u64 = 1 << 0;
+pub const DNG_API_FAMILY_AUDIO: dng_u64 = 1 << 1;
 
 pub type dng_bool_v1 = dng_u8;
+pub const DNG_BOOL_FALSE: dng_bool_v1 = 0;
+pub const DNG_BOOL_TRUE: dng_bool_v1 = 1;
 
 #[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct dng_abi_header_v1 {
     pub struct_size: dng_u32,
     pub abi_version: dng_u32,
@@ -39,6 +81,37 @@ pub struct dng_str_view_v1 {
 
 pub type dng_window_handle_v1 = dng_u64;
 
+pub type dng_pointer_device_kind_v1 = dng_u32;
+pub const DNG_PTR_MOUSE: dng_pointer_device_kind_v1 = 0;
+pub const DNG_PTR_TOUCH: dng_pointer_device_kind_v1 = 1;
+pub const DNG_PTR_STYLUS: dng_pointer_device_kind_v1 = 2;
+
+pub type dng_media_key_v1 = dng_u32;
+pub const DNG_MEDIA_KEY_PLAY: dng_media_key_v1 = 1 << 0;
+pub const DNG_MEDIA_KEY_PAUSE: dng_media_key_v1 = 1 << 1;
+pub const DNG_MEDIA_KEY_NEXT: dng_media_key_v1 = 1 << 2;
+pub const DNG_MEDIA_KEY_PREV: dng_media_key_v1 = 1 << 3;
+const DNG_MEDIA_KEY_ALL: dng_u32 = DNG_MEDIA_KEY_PLAY | DNG_MEDIA_KEY_PAUSE | DNG_MEDIA_KEY_NEXT | DNG_MEDIA_KEY_PREV;
+
+pub type dng_announce_priority_v1 = dng_u32;
+pub const DNG_ANNOUNCE_POLITE: dng_announce_priority_v1 = 0;
+pub const DNG_ANNOUNCE_ASSERTIVE: dng_announce_priority_v1 = 1;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct dng_announcement_v1 {
+    pub handle: dng_window_handle_v1,
+    pub priority: dng_announce_priority_v1,
+    pub text: dng_str_view_v1,
+}
+
+pub const DNG_WINDOW_FLAG_RESIZABLE: dng_u32 = 1 << 0;
+pub const DNG_WINDOW_FLAG_BORDERLESS: dng_u32 = 1 << 1;
+pub const DNG_WINDOW_FLAG_HIDDEN: dng_u32 = 1 << 2;
+pub const DNG_WINDOW_FLAG_FULLSCREEN: dng_u32 = 1 << 3;
+const DNG_WINDOW_FLAG_ALL: dng_u32 =
+    DNG_WINDOW_FLAG_RESIZABLE | DNG_WINDOW_FLAG_BORDERLESS | DNG_WINDOW_FLAG_HIDDEN | DNG_WINDOW_FLAG_FULLSCREEN;
+
 #[repr(C)]
 pub struct dng_window_desc_v1 {
     pub width: dng_u32,
@@ -54,6 +127,93 @@ pub struct dng_window_size_v1 {
     pub height: dng_u32,
 }
 
+// Pixel rectangle on a window surface; zero width or height means empty.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct dng_rect_v1 {
+    pub x: i32,
+    pub y: i32,
+    pub width: dng_u32,
+    pub height: dng_u32,
+}
+
+pub const DNG_RENDERER_NONE: dng_u32 = 0;
+pub const DNG_RENDERER_VULKAN: dng_u32 = 1;
+pub const DNG_RENDERER_METAL: dng_u32 = 2;
+pub const DNG_RENDERER_D3D12: dng_u32 = 3;
+
+// Renderer attached to a window; vendor/device ids are PCI ids, 0 when unknown.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct dng_renderer_info_v1 {
+    pub kind: dng_u32,
+    pub api_version: dng_u32,
+    pub vendor_id: dng_u32,
+    pub device_id: dng_u32,
+}
+
+pub type dng_window_event_type_v1 = dng_u32;
+pub const DNG_EVENT_NONE: dng_window_event_type_v1 = 0;
+pub const DNG_EVENT_RESIZE: dng_window_event_type_v1 = 1;
+pub const DNG_EVENT_CLOSE: dng_window_event_type_v1 = 2;
+pub const DNG_EVENT_FOCUS: dng_window_event_type_v1 = 3;
+pub const DNG_EVENT_IPC: dng_window_event_type_v1 = 4;
+// Module-scope event (handle 0): the session is ending and the host should save and quit.
+pub const DNG_EVENT_QUIT_REQUESTED: dng_window_event_type_v1 = 5;
+// Pointer events carry dng_window_pointer_v1 in window surface coordinates.
+pub const DNG_EVENT_TOUCH_DOWN: dng_window_event_type_v1 = 6;
+pub const DNG_EVENT_TOUCH_MOVE: dng_window_event_type_v1 = 7;
+pub const DNG_EVENT_TOUCH_UP: dng_window_event_type_v1 = 8;
+pub const DNG_EVENT_MOUSE_DOWN: dng_window_event_type_v1 = 9;
+pub const DNG_EVENT_MOUSE_MOVE: dng_window_event_type_v1 = 10;
+pub const DNG_EVENT_MOUSE_UP: dng_window_event_type_v1 = 11;
+
+// IPC payloads are copied into the event itself, so they are capped.
+pub const DNG_IPC_PAYLOAD_MAX: dng_u32 = 16;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct dng_window_focus_v1 {
+    pub focused: dng_bool_v1,
+}
+
+// `touch_id` identifies the finger for touch events and is 0 for mouse events.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct dng_window_pointer_v1 {
+    pub x: i32,
+    pub y: i32,
+    pub touch_id: dng_u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct dng_window_ipc_v1 {
+    pub source: dng_window_handle_v1,
+    pub kind: dng_u32,
+    pub size: dng_u32,
+    pub data: [dng_u8; DNG_IPC_PAYLOAD_MAX as usize],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union dng_window_event_payload_v1 {
+    pub size: dng_window_size_v1,
+    pub focus: dng_window_focus_v1,
+    pub ipc: dng_window_ipc_v1,
+    pub pointer: dng_window_pointer_v1,
+    pub reserved: [dng_u64; 4],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct dng_window_event_v1 {
+    pub event_type: dng_window_event_type_v1,
+    pub handle: dng_window_handle_v1,
+    pub timestamp: dng_u64,
+    pub payload: dng_window_event_payload_v1,
+}
+
 #[repr(C)]
 pub struct dng_host_api_v1 {
     pub header: dng_abi_header_v1,
@@ -74,6 +234,95 @@ pub struct dng_window_api_v1 {
     pub set_title: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_str_view_v1) -> dng_status_v1>,
 }
 
+// v1 prefix (header through set_title) followed by the v2 extension callbacks.
+#[repr(C)]
+pub struct dng_window_api_v2 {
+    pub header: dng_abi_header_v1,
+    pub ctx: *mut c_void,
+    pub create: Option<extern "C" fn(*mut c_void, *const dng_window_desc_v1, *mut dng_window_handle_v1) -> dng_status_v1>,
+    pub destroy: Option<extern "C" fn(*mut c_void, dng_window_handle_v1) -> dng_status_v1>,
+    pub poll: Option<extern "C" fn(*mut c_void) -> dng_status_v1>,
+    pub get_size: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_window_size_v1) -> dng_status_v1>,
+    pub set_title: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_str_view_v1) -> dng_status_v1>,
+    pub set_frame_callback_thread: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_u64) -> dng_status_v1>,
+    pub get_frame_callback_thread: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_u64) -> dng_status_v1>,
+    pub get_title: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut c_char, dng_u32, *mut dng_u32) -> dng_status_v1>,
+    pub get_pointer_device_kind: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_pointer_device_kind_v1) -> dng_status_v1>,
+    pub set_size: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *const dng_window_size_v1) -> dng_status_v1>,
+    pub get_media_key_state: Option<extern "C" fn(*mut c_void, *mut dng_u32) -> dng_status_v1>,
+    pub poll_event: Option<extern "C" fn(*mut c_void, *mut dng_window_event_v1) -> dng_status_v1>,
+    pub announce: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_str_view_v1, dng_announce_priority_v1) -> dng_status_v1>,
+    pub set_window_task_description: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_str_view_v1) -> dng_status_v1>,
+    pub get_window_task_description:
+        Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut c_char, dng_u32, *mut dng_u32) -> dng_status_v1>,
+    pub get_flags: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_u32) -> dng_status_v1>,
+    pub set_parent: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_window_handle_v1) -> dng_status_v1>,
+    pub get_ancestor_chain:
+        Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_window_handle_v1, dng_u32, *mut dng_u32) -> dng_status_v1>,
+    pub get_window_depth: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_u32) -> dng_status_v1>,
+    pub send_ipc_message: Option<
+        extern "C" fn(*mut c_void, dng_window_handle_v1, dng_window_handle_v1, dng_u32, *const c_void, dng_u32) -> dng_status_v1,
+    >,
+    pub mark_dirty: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_rect_v1) -> dng_status_v1>,
+    pub get_and_clear_dirty: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_rect_v1) -> dng_status_v1>,
+    pub request_quit: Option<extern "C" fn(*mut c_void) -> dng_status_v1>,
+    pub get_quit_requested: Option<extern "C" fn(*mut c_void, *mut dng_bool_v1) -> dng_status_v1>,
+    pub cancel_quit: Option<extern "C" fn(*mut c_void) -> dng_status_v1>,
+    pub set_touch_to_mouse_emulation: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_bool_v1) -> dng_status_v1>,
+    pub set_time_base: Option<extern "C" fn(*mut c_void, dng_u64, dng_u64) -> dng_status_v1>,
+    pub convert_time: Option<extern "C" fn(*mut c_void, dng_u64, *mut dng_u64) -> dng_status_v1>,
+    pub get_renderer_info: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_renderer_info_v1) -> dng_status_v1>,
+    pub set_window_aspect_hint: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_u32, dng_u32) -> dng_status_v1>,
+    pub get_window_aspect_hint: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_u32, *mut dng_u32) -> dng_status_v1>,
+    pub get_is_main_window: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, *mut dng_bool_v1) -> dng_status_v1>,
+    pub set_is_main_window: Option<extern "C" fn(*mut c_void, dng_window_handle_v1, dng_bool_v1) -> dng_status_v1>,
+}
+
+// Filled by dngModuleQueryInfo before any host API exists; header describes
+// this struct itself.
+#[repr(C)]
+pub struct dng_module_info {
+    pub header: dng_abi_header_v1,
+    pub module_name: dng_str_view_v1,
+    pub module_version_major: dng_u32,
+    pub module_version_minor: dng_u32,
+    pub module_version_patch: dng_u32,
+    pub min_abi_version: dng_u32,
+    pub max_abi_version: dng_u32,
+    pub api_families: dng_u64,
+}
+
+// Filled by dngModuleQueryStructSizes for one supported ABI version; a size of
+// 0 means the struct does not exist in that version.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct dng_abi_struct_sizes_v1 {
+    pub header: dng_abi_header_v1,
+    pub host_api: dng_u32,
+    pub module_api: dng_u32,
+    pub window_api: dng_u32,
+    pub window_desc: dng_u32,
+    pub window_event: dng_u32,
+    pub module_info: dng_u32,
+    pub audio_api: dng_u32,
+}
+
+pub type dng_audio_device_handle_v1 = dng_u64;
+
+// Null audio sub-API. Shares the module context (and its lock) with the window
+// API; `ctx` is the same pointer as dng_window_api_v2.ctx.
+#[repr(C)]
+pub struct dng_audio_api_v1 {
+    pub header: dng_abi_header_v1,
+    pub ctx: *mut c_void,
+    pub open_device: Option<extern "C" fn(*mut c_void, dng_u32, dng_u32, *mut dng_audio_device_handle_v1) -> dng_status_v1>,
+    pub close_device: Option<extern "C" fn(*mut c_void, dng_audio_device_handle_v1) -> dng_status_v1>,
+    // Interleaved f32 samples: frame_count * channels values at sample_rate.
+    pub submit_buffer: Option<extern "C" fn(*mut c_void, dng_audio_device_handle_v1, *const f32, dng_u32, dng_u32) -> dng_status_v1>,
+    pub get_latency: Option<extern "C" fn(*mut c_void, dng_audio_device_handle_v1, *mut dng_u64) -> dng_status_v1>,
+    pub get_submitted_frames: Option<extern "C" fn(*mut c_void, dng_audio_device_handle_v1, *mut dng_u64) -> dng_status_v1>,
+}
+
 #[repr(C)]
 pub struct dng_module_api_v1 {
     pub header: dng_abi_header_v1,
@@ -85,213 +334,3815 @@ pub struct dng_module_api_v1 {
     pub shutdown: Option<extern "C" fn(*mut c_void, *const dng_host_api_v1) -> dng_status_v1>,
 }
 
+// Returned by dngModuleGetApi_v2. Same leading fields as v1, with the window
+// table replaced by its v2 superset and the audio sub-API after shutdown.
 #[repr(C)]
-struct NullWindowCtx {
-    host: *const dng_host_api_v1,
+pub struct dng_module_api_v2 {
+    pub header: dng_abi_header_v1,
+    pub module_name: dng_str_view_v1,
+    pub module_version_major: dng_u32,
+    pub module_version_minor: dng_u32,
+    pub module_version_patch: dng_u32,
+    pub window: dng_window_api_v2,
+    pub shutdown: Option<extern "C" fn(*mut c_void, *const dng_host_api_v1) -> dng_status_v1>,
+    pub audio: dng_audio_api_v1,
+}
+
+const MODULE_NAME: &[u8] = b"RustNullWindow";
+const MODULE_VERSION_MAJOR: dng_u32 = 1;
+const MODULE_VERSION_MINOR: dng_u32 = 0;
+const MODULE_VERSION_PATCH: dng_u32 = 0;
+
+// Upper bound on simultaneously live windows; create reports
+// DNG_STATUS_OUT_OF_MEMORY once the record table would have to grow past it.
+const MAX_WINDOWS: dng_u32 = 65536;
+const INITIAL_WINDOW_CAPACITY: dng_u32 = 4;
+// Largest title (and announcement) accepted, in bytes.
+const MAX_TITLE_SIZE: dng_u32 = 64 * 1024;
+// Default engine clock: host nanoseconds, one tick per nanosecond.
+const NANOS_PER_SECOND: dng_u64 = 1_000_000_000;
+// Size reported by get_size for DNG_WINDOW_FLAG_FULLSCREEN windows.
+const VIRTUAL_DISPLAY_SIZE: dng_window_size_v1 = dng_window_size_v1 { width: 1920, height: 1080 };
+// Upper bound on pending events; injection reports DNG_STATUS_OUT_OF_MEMORY
+// once the queue is full.
+const MAX_QUEUED_EVENTS: dng_u32 = 4096;
+const INITIAL_EVENT_CAPACITY: dng_u32 = 16;
+// How long shutdown waits for calls racing it to leave before it gives up
+// with DNG_STATUS_FAIL, and how often it re-checks.
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+const SHUTDOWN_DRAIN_POLL: Duration = Duration::from_micros(100);
+// Upper bound on recorded announcements; announce reports
+// DNG_STATUS_OUT_OF_MEMORY once reached until the record is cleared.
+const MAX_ANNOUNCEMENTS: dng_u32 = 4096;
+const INITIAL_ANNOUNCEMENT_CAPACITY: dng_u32 = 8;
+
+const MAX_AUDIO_DEVICES: dng_u32 = 64;
+const INITIAL_AUDIO_DEVICE_CAPACITY: dng_u32 = 2;
+const MAX_AUDIO_CHANNELS: dng_u32 = 8;
+// Latency every null audio device reports, in microseconds.
+const NULL_AUDIO_LATENCY_US: dng_u64 = 10_000;
+
+#[repr(C)]
+struct NullAudioDevice {
+    handle: dng_audio_device_handle_v1,
+    sample_rate: dng_u32,
+    channels: dng_u32,
+    frames_submitted: dng_u64,
+}
+
+#[repr(C)]
+struct NullWindowRecord {
     handle: dng_window_handle_v1,
+    // Windowed size; fullscreen windows report VIRTUAL_DISPLAY_SIZE instead.
     size: dng_window_size_v1,
+    flags: dng_u32,
+    // Parent window; 0 for top-level windows.
+    parent: dng_window_handle_v1,
     title: *mut c_char,
     title_size: dng_u32,
+    // Thread that should fire frame callbacks; 0 means the calling thread.
+    frame_callback_thread: dng_u64,
+    // Device kind of the last pointer input injected into this window.
+    pointer_device_kind: dng_pointer_device_kind_v1,
+    // Accessibility task description, stored independently of the title.
+    task_description: *mut c_char,
+    task_description_size: dng_u32,
+    // Union of surface regions marked dirty since the last get_and_clear_dirty.
+    dirty: dng_rect_v1,
+    // When set, the first active touch is mirrored as DNG_EVENT_MOUSE_* events.
+    touch_to_mouse: bool,
+    primary_touch_active: bool,
+    primary_touch: dng_u32,
+    // Preferred aspect ratio for layout managers; 0/0 means no hint. Never enforced.
+    aspect_numer: dng_u32,
+    aspect_denom: dng_u32,
+}
+
+#[repr(C)]
+struct NullAnnouncementRecord {
+    handle: dng_window_handle_v1,
+    priority: dng_announce_priority_v1,
+    text: *mut c_char,
+    text_size: dng_u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct NullWindowSlot {
+    // Bumped on every destroy so handles to the previous occupant stop matching.
+    generation: dng_u32,
+    live: bool,
+    // Index into `windows` while live; next free slot (or NO_SLOT) otherwise.
+    link: dng_u32,
+}
+
+const NO_SLOT: dng_u32 = dng_u32::MAX;
+
+#[repr(C)]
+struct NullWindowState {
+    host: *const dng_host_api_v1,
+    // False when the host left alloc/free unset and std::alloc backs all storage.
+    uses_host_alloc: bool,
+    windows: *mut NullWindowRecord,
+    window_count: dng_u32,
+    window_capacity: dng_u32,
+    // Slot table indexed by dng_handle_slot; never shrinks within a session.
+    slots: *mut NullWindowSlot,
+    slot_count: dng_u32,
+    slot_capacity: dng_u32,
+    // Head of the free-slot list threaded through NullWindowSlot::link.
+    free_slot: dng_u32,
+    // Bitmask of DNG_MEDIA_KEY_* currently held down.
+    media_keys: dng_u32,
+    // FIFO ring buffer of pending events.
+    events: *mut dng_window_event_v1,
+    event_head: dng_u32,
+    event_count: dng_u32,
+    event_capacity: dng_u32,
+    // Announcements recorded since the last clear, oldest first.
+    announcements: *mut NullAnnouncementRecord,
+    announcement_count: dng_u32,
+    announcement_capacity: dng_u32,
+    audio_devices: *mut NullAudioDevice,
+    audio_device_count: dng_u32,
+    audio_device_capacity: dng_u32,
+    // Audio device handles are never reused within a session; 0 stays invalid.
+    next_audio_device: dng_audio_device_handle_v1,
+    // Set by request_quit and held until cancel_quit.
+    quit_requested: bool,
+    // The single main window, or 0 when none is designated.
+    main_window: dng_window_handle_v1,
+    // Engine clock mapping: host nanoseconds at engine tick 0 and the tick rate.
+    time_zero_ns: dng_u64,
+    ticks_per_second: dng_u64,
+    // The null module has no clock; host time is the timestamp of the last
+    // injected event, and every queued event is stamped from it.
+    host_now_ns: dng_u64,
+    // Set by shutdown; calls that take the lock afterwards fail without
+    // touching the rest of the state.
+    closing: bool,
+}
+
+// Opaque context handed to the host. All mutable state sits behind one lock,
+// so the API may be called from any thread.
+struct NullWindowCtx {
+    state: Mutex<NullWindowState>,
+    // Entry points currently using the context, including ones still waiting
+    // for the lock; shutdown drains this under the lock before freeing.
+    in_flight: AtomicU32,
+}
+
+// Counts one entry point as in flight until dropped.
+struct InFlightCall<'a>(&'a AtomicU32);
+
+impl Drop for InFlightCall<'_> {
+    fn drop(&mut self) {
+        // Last access to the context: shutdown may free it right after this.
+        self.0.fetch_sub(1, Ordering::Release);
+    }
+}
+
+// Fields drop in order, so the lock is released before the call stops
+// counting as in flight.
+struct CtxGuard<'a> {
+    state: MutexGuard<'a, NullWindowState>,
+    _call: InFlightCall<'a>,
+}
+
+impl core::ops::Deref for CtxGuard<'_> {
+    type Target = NullWindowState;
+
+    fn deref(&self) -> &NullWindowState {
+        &self.state
+    }
+}
+
+impl core::ops::DerefMut for CtxGuard<'_> {
+    fn deref_mut(&mut self) -> &mut NullWindowState {
+        &mut self.state
+    }
+}
+
+fn lock_state(ctx: &NullWindowCtx) -> MutexGuard<'_, NullWindowState> {
+    ctx.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// Locks the context for the duration of one entry point, or returns None once
+// shutdown has begun. A poisoned lock only means an earlier call panicked (and
+// reported DNG_STATUS_FAIL); later calls keep using the state rather than
+// failing forever.
+unsafe fn lock_ctx<'a>(raw_ctx: *mut c_void) -> Option<CtxGuard<'a>> {
+    let ctx = &*(raw_ctx as *const NullWindowCtx);
+    ctx.in_flight.fetch_add(1, Ordering::Acquire);
+    let call = InFlightCall(&ctx.in_flight);
+    let state = lock_state(ctx);
+    if state.closing {
+        return None;
+    }
+    Some(CtxGuard { state, _call: call })
 }
 
-unsafe fn log_message(host: *const dng_host_api_v1, level: dng_u32, msg: &'static [u8]) {
+// Routes module allocations to the host callbacks or, when the host provides
+// neither, to the Rust global allocator.
+#[derive(Clone, Copy)]
+struct NullAllocator {
+    host: *const dng_host_api_v1,
+    uses_host_alloc: bool,
+}
+
+impl NullAllocator {
+    unsafe fn alloc(self, size: dng_u64, align: dng_u64) -> *mut c_void {
+        let mem = if self.uses_host_alloc {
+            match (*self.host).alloc {
+                Some(alloc_fn) => alloc_fn((*self.host).user, size, align),
+                None => ptr::null_mut(),
+            }
+        } else {
+            match Layout::from_size_align(size as usize, align as usize) {
+                Ok(layout) if layout.size() > 0 => std::alloc::alloc(layout) as *mut c_void,
+                _ => ptr::null_mut(),
+            }
+        };
+        if mem.is_null() {
+            log_message(self.host, DNG_LOG_LEVEL_ERROR, format_args!("allocation of {} bytes (align {}) failed", size, align));
+        }
+        mem
+    }
+
+    unsafe fn free(self, mem: *mut c_void, size: dng_u64, align: dng_u64) {
+        if mem.is_null() {
+            return;
+        }
+        if self.uses_host_alloc {
+            if let Some(free_fn) = (*self.host).free {
+                free_fn((*self.host).user, mem, size, align);
+            }
+            return;
+        }
+        if let Ok(layout) = Layout::from_size_align(size as usize, align as usize) {
+            std::alloc::dealloc(mem as *mut u8, layout);
+        }
+    }
+}
+
+fn allocator(ctx: &NullWindowState) -> NullAllocator {
+    NullAllocator { host: ctx.host, uses_host_alloc: ctx.uses_host_alloc }
+}
+
+const LOG_BUFFER_SIZE: usize = 256;
+
+// Fixed stack buffer for log formatting; output past the end is dropped at a
+// UTF-8 boundary so the message stays valid text.
+struct LogBuffer {
+    data: [u8; LOG_BUFFER_SIZE],
+    len: usize,
+}
+
+impl fmt::Write for LogBuffer {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let mut take = text.len().min(LOG_BUFFER_SIZE - self.len);
+        while !text.is_char_boundary(take) {
+            take -= 1;
+        }
+        self.data[self.len..self.len + take].copy_from_slice(&text.as_bytes()[..take]);
+        self.len += take;
+        Ok(())
+    }
+}
+
+// Logs handles as slot:generation.
+struct LogHandle(dng_window_handle_v1);
+
+impl fmt::Display for LogHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", dng_handle_slot(self.0), dng_handle_generation(self.0))
+    }
+}
+
+// Formats and forwards a message when the host provides a log callback. The
+// callback may run with the context lock held and must not call back into
+// the module.
+unsafe fn log_message(host: *const dng_host_api_v1, level: dng_u32, args: fmt::Arguments) {
     if host.is_null() {
         return;
     }
     let h = &*host;
     if let Some(log_fn) = h.log {
-        let view = dng_str_view_v1 { data: msg.as_ptr() as *const c_char, size: msg.len() as dng_u32 };
+        let mut buffer = LogBuffer { data: [0; LOG_BUFFER_SIZE], len: 0 };
+        let _ = fmt::write(&mut buffer, args);
+        let view = dng_str_view_v1 { data: buffer.data.as_ptr() as *const c_char, size: buffer.len as dng_u32 };
         log_fn(h.user, level, view);
     }
 }
 
-unsafe fn free_title(ctx: &mut NullWindowCtx) {
-    if !ctx.title.is_null() {
-        if let Some(free_fn) = (*ctx.host).free {
-            free_fn((*ctx.host).user, ctx.title as *mut c_void, ctx.title_size as dng_u64, 1);
-        }
-        ctx.title = ptr::null_mut();
-        ctx.title_size = 0;
+unsafe fn free_str(alloc: NullAllocator, data: &mut *mut c_char, size: &mut dng_u32) {
+    if !data.is_null() {
+        alloc.free(*data as *mut c_void, *size as dng_u64, 1);
+        *data = ptr::null_mut();
+        *size = 0;
+    }
+}
+
+unsafe fn alloc_copy_str(alloc: NullAllocator, view: dng_str_view_v1, data: &mut *mut c_char, size: &mut dng_u32) -> dng_status_v1 {
+    if view.size == 0 {
+        return DNG_STATUS_OK;
+    }
+    if view.data.is_null() {
+        return DNG_STATUS_INVALID_ARG;
+    }
+    let mem = alloc.alloc(view.size as dng_u64, 1);
+    if mem.is_null() {
+        return DNG_STATUS_OUT_OF_MEMORY;
+    }
+    ptr::copy_nonoverlapping(view.data, mem as *mut c_char, view.size as usize);
+    *data = mem as *mut c_char;
+    *size = view.size;
+    DNG_STATUS_OK
+}
+
+// Copies `view` before freeing the stored string, so a failed copy keeps the
+// previous value.
+unsafe fn replace_str(alloc: NullAllocator, view: dng_str_view_v1, data: &mut *mut c_char, size: &mut dng_u32) -> dng_status_v1 {
+    let (mut new_data, mut new_size) = (ptr::null_mut(), 0);
+    let status = alloc_copy_str(alloc, view, &mut new_data, &mut new_size);
+    if status != DNG_STATUS_OK {
+        return status;
     }
+    free_str(alloc, data, size);
+    *data = new_data;
+    *size = new_size;
+    DNG_STATUS_OK
+}
+
+unsafe fn free_window_resources(alloc: NullAllocator, window: &mut NullWindowRecord) {
+    free_str(alloc, &mut window.title, &mut window.title_size);
+    free_str(alloc, &mut window.task_description, &mut window.task_description_size);
 }
 
-unsafe fn alloc_copy_title(ctx: &mut NullWindowCtx, title: dng_str_view_v1) -> dng_status_v1 {
+// Titles must be UTF-8 and at most MAX_TITLE_SIZE bytes. Interior NULs are
+// kept as-is because string views are length-prefixed.
+unsafe fn validate_title(title: dng_str_view_v1) -> dng_status_v1 {
     if title.size == 0 {
         return DNG_STATUS_OK;
     }
-    if title.data.is_null() {
+    if title.data.is_null() || title.size > MAX_TITLE_SIZE {
         return DNG_STATUS_INVALID_ARG;
     }
-    let alloc_fn = match (*ctx.host).alloc {
-        Some(f) => f,
-        None => return DNG_STATUS_INVALID_ARG,
-    };
-    let size = title.size as dng_u64;
-    let mem = alloc_fn((*ctx.host).user, size, 1);
+    let bytes = core::slice::from_raw_parts(title.data as *const dng_u8, title.size as usize);
+    match core::str::from_utf8(bytes) {
+        Ok(_) => DNG_STATUS_OK,
+        Err(_) => DNG_STATUS_INVALID_UTF8,
+    }
+}
+
+unsafe fn alloc_copy_title(alloc: NullAllocator, window: &mut NullWindowRecord, title: dng_str_view_v1) -> dng_status_v1 {
+    alloc_copy_str(alloc, title, &mut window.title, &mut window.title_size)
+}
+
+// Ensures room for one more element, doubling the allocation up to `max`.
+unsafe fn grow_array<T>(
+    alloc: NullAllocator,
+    items: &mut *mut T,
+    count: dng_u32,
+    capacity: &mut dng_u32,
+    initial: dng_u32,
+    max: dng_u32,
+) -> dng_status_v1 {
+    if count < *capacity {
+        return DNG_STATUS_OK;
+    }
+    if *capacity >= max {
+        return DNG_STATUS_OUT_OF_MEMORY;
+    }
+    let new_capacity = if *capacity == 0 { initial } else { (*capacity * 2).min(max) };
+    let item_size = size_of::<T>() as dng_u64;
+    let item_align = align_of::<T>() as dng_u64;
+    let mem = alloc.alloc(item_size * new_capacity as dng_u64, item_align) as *mut T;
     if mem.is_null() {
         return DNG_STATUS_OUT_OF_MEMORY;
     }
-    ptr::copy_nonoverlapping(title.data, mem as *mut c_char, title.size as usize);
-    ctx.title = mem as *mut c_char;
-    ctx.title_size = title.size;
+    if !items.is_null() {
+        ptr::copy_nonoverlapping(*items, mem, count as usize);
+        alloc.free(*items as *mut c_void, item_size * *capacity as dng_u64, item_align);
+    }
+    *items = mem;
+    *capacity = new_capacity;
     DNG_STATUS_OK
 }
 
-fn catch_unwind_status<F: FnOnce() -> dng_status_v1 + UnwindSafe>(f: F) -> dng_status_v1 {
-    match catch_unwind(f) {
-        Ok(s) => s,
-        Err(_) => DNG_STATUS_FAIL,
+unsafe fn free_array<T>(alloc: NullAllocator, items: &mut *mut T, capacity: &mut dng_u32) {
+    if !items.is_null() {
+        alloc.free(*items as *mut c_void, size_of::<T>() as dng_u64 * *capacity as dng_u64, align_of::<T>() as dng_u64);
+        *items = ptr::null_mut();
     }
+    *capacity = 0;
 }
 
-extern "C" fn window_create(raw_ctx: *mut c_void, desc: *const dng_window_desc_v1, out_handle: *mut dng_window_handle_v1) -> dng_status_v1 {
-    catch_unwind_status(|| unsafe {
-        if raw_ctx.is_null() || desc.is_null() || out_handle.is_null() {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        let ctx = &mut *(raw_ctx as *mut NullWindowCtx);
-        if ctx.handle != 0 {
-            return DNG_STATUS_FAIL;
-        }
-        let d = &*desc;
-        if d.flags != 0 {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        if d.title.size > 0 && d.title.data.is_null() {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        ctx.size.width = d.width;
-        ctx.size.height = d.height;
-        free_title(ctx);
-        let title_status = alloc_copy_title(ctx, d.title);
-        if title_status != DNG_STATUS_OK {
-            return title_status;
-        }
-        ctx.handle = 1;
-        *out_handle = ctx.handle;
-        DNG_STATUS_OK
-    })
+unsafe fn windows_mut(ctx: &mut NullWindowState) -> &mut [NullWindowRecord] {
+    if ctx.windows.is_null() {
+        return &mut [];
+    }
+    core::slice::from_raw_parts_mut(ctx.windows, ctx.window_count as usize)
 }
 
-extern "C" fn window_destroy(raw_ctx: *mut c_void, handle: dng_window_handle_v1) -> dng_status_v1 {
-    catch_unwind_status(|| unsafe {
-        if raw_ctx.is_null() || handle == 0 {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        let ctx = &mut *(raw_ctx as *mut NullWindowCtx);
-        if ctx.handle != handle {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        free_title(ctx);
-        ctx.handle = 0;
-        ctx.size.width = 0;
-        ctx.size.height = 0;
-        DNG_STATUS_OK
-    })
+unsafe fn slot_mut(ctx: &mut NullWindowState, slot: dng_u32) -> &mut NullWindowSlot {
+    &mut *ctx.slots.add(slot as usize)
 }
 
-extern "C" fn window_poll(raw_ctx: *mut c_void) -> dng_status_v1 {
-    catch_unwind_status(|| unsafe {
-        if raw_ctx.is_null() {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        DNG_STATUS_OK
-    })
+unsafe fn find_window(ctx: &mut NullWindowState, handle: dng_window_handle_v1) -> Option<usize> {
+    let slot = dng_handle_slot(handle);
+    if slot >= ctx.slot_count {
+        return None;
+    }
+    let entry = *slot_mut(ctx, slot);
+    if !entry.live || entry.generation != dng_handle_generation(handle) {
+        return None;
+    }
+    Some(entry.link as usize)
 }
 
-extern "C" fn window_get_size(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_size: *mut dng_window_size_v1) -> dng_status_v1 {
-    catch_unwind_status(|| unsafe {
-        if raw_ctx.is_null() || out_size.is_null() || handle == 0 {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        let ctx = &*(raw_ctx as *mut NullWindowCtx);
-        if ctx.handle != handle {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        ptr::write(out_size, ctx.size);
-        DNG_STATUS_OK
-    })
+unsafe fn window_mut(ctx: &mut NullWindowState, handle: dng_window_handle_v1) -> Option<&mut NullWindowRecord> {
+    let index = find_window(ctx, handle)?;
+    Some(&mut windows_mut(ctx)[index])
 }
 
-extern "C" fn window_set_title(raw_ctx: *mut c_void, handle: dng_window_handle_v1, title: dng_str_view_v1) -> dng_status_v1 {
-    catch_unwind_status(|| unsafe {
-        if raw_ctx.is_null() || handle == 0 {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        let ctx = &mut *(raw_ctx as *mut NullWindowCtx);
-        if ctx.handle != handle {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        if title.size > 0 && title.data.is_null() {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        free_title(ctx);
-        alloc_copy_title(ctx, title)
-    })
+unsafe fn parent_of(ctx: &mut NullWindowState, handle: dng_window_handle_v1) -> dng_window_handle_v1 {
+    match window_mut(ctx, handle) {
+        Some(w) => w.parent,
+        None => 0,
+    }
 }
 
-extern "C" fn module_shutdown(raw_ctx: *mut c_void, host: *const dng_host_api_v1) -> dng_status_v1 {
-    catch_unwind_status(|| unsafe {
-        if raw_ctx.is_null() || host.is_null() {
-            return DNG_STATUS_INVALID_ARG;
-        }
-        let ctx = &mut *(raw_ctx as *mut NullWindowCtx);
-        free_title(ctx);
-        let free_fn = match (*host).free {
-            Some(f) => f,
-            None => return DNG_STATUS_INVALID_ARG,
-        };
-        free_fn((*host).user, raw_ctx, size_of::<NullWindowCtx>() as dng_u64, align_of::<NullWindowCtx>() as dng_u64);
-        DNG_STATUS_OK
-    })
+unsafe fn window_depth(ctx: &mut NullWindowState, handle: dng_window_handle_v1) -> dng_u32 {
+    let mut depth: dng_u32 = 0;
+    let mut ancestor = parent_of(ctx, handle);
+    while ancestor != 0 {
+        depth += 1;
+        ancestor = parent_of(ctx, ancestor);
+    }
+    depth
 }
 
-#[no_mangle]
-pub extern "C" fn dngModuleGetApi_v1(host: *const dng_host_api_v1, out_api: *mut dng_module_api_v1) -> dng_status_v1 {
-    catch_unwind_status(|| unsafe {
-        if host.is_null() || out_api.is_null() {
-            return DNG_STATUS_INVALID_ARG;
+const EMPTY_RECT: dng_rect_v1 = dng_rect_v1 { x: 0, y: 0, width: 0, height: 0 };
+
+fn surface_size(window: &NullWindowRecord) -> dng_window_size_v1 {
+    if window.flags & DNG_WINDOW_FLAG_FULLSCREEN != 0 {
+        VIRTUAL_DISPLAY_SIZE
+    } else {
+        window.size
+    }
+}
+
+fn full_rect(size: dng_window_size_v1) -> dng_rect_v1 {
+    dng_rect_v1 { x: 0, y: 0, width: size.width, height: size.height }
+}
+
+fn rect_is_empty(rect: dng_rect_v1) -> bool {
+    rect.width == 0 || rect.height == 0
+}
+
+// Intersects `rect` with the surface; edges are computed in i64 so huge
+// extents cannot overflow.
+fn clamp_rect(rect: dng_rect_v1, bounds: dng_window_size_v1) -> dng_rect_v1 {
+    let x0 = (rect.x as i64).max(0);
+    let y0 = (rect.y as i64).max(0);
+    let x1 = (rect.x as i64 + rect.width as i64).min(bounds.width as i64);
+    let y1 = (rect.y as i64 + rect.height as i64).min(bounds.height as i64);
+    if x1 <= x0 || y1 <= y0 {
+        return EMPTY_RECT;
+    }
+    dng_rect_v1 { x: x0 as i32, y: y0 as i32, width: (x1 - x0) as dng_u32, height: (y1 - y0) as dng_u32 }
+}
+
+// Smallest rectangle covering both inputs; both must already be clamped, so
+// the union fits the surface. Edges are computed in i64 like clamp_rect.
+fn union_rect(a: dng_rect_v1, b: dng_rect_v1) -> dng_rect_v1 {
+    if rect_is_empty(a) {
+        return b;
+    }
+    if rect_is_empty(b) {
+        return a;
+    }
+    let x0 = a.x.min(b.x);
+    let y0 = a.y.min(b.y);
+    let x1 = (a.x as i64 + a.width as i64).max(b.x as i64 + b.width as i64);
+    let y1 = (a.y as i64 + a.height as i64).max(b.y as i64 + b.height as i64);
+    dng_rect_v1 { x: x0, y: y0, width: (x1 - x0 as i64) as dng_u32, height: (y1 - y0 as i64) as dng_u32 }
+}
+
+// Makes room for one more record and one more slot, and returns the handle the
+// next window will get. Nothing is committed until claim_slot.
+unsafe fn reserve_window(ctx: &mut NullWindowState) -> Result<dng_window_handle_v1, dng_status_v1> {
+    let alloc = allocator(ctx);
+    let status = grow_array(alloc, &mut ctx.windows, ctx.window_count, &mut ctx.window_capacity, INITIAL_WINDOW_CAPACITY, MAX_WINDOWS);
+    if status != DNG_STATUS_OK {
+        return Err(status);
+    }
+    if ctx.free_slot != NO_SLOT {
+        let slot = ctx.free_slot;
+        return Ok(dng_make_handle(slot, slot_mut(ctx, slot).generation));
+    }
+    let status = grow_array(alloc, &mut ctx.slots, ctx.slot_count, &mut ctx.slot_capacity, INITIAL_WINDOW_CAPACITY, MAX_WINDOWS);
+    if status != DNG_STATUS_OK {
+        return Err(status);
+    }
+    ptr::write(ctx.slots.add(ctx.slot_count as usize), NullWindowSlot { generation: 1, live: false, link: NO_SLOT });
+    Ok(dng_make_handle(ctx.slot_count, 1))
+}
+
+// Marks the slot reserved for `handle` live, pointing at record `index`.
+unsafe fn claim_slot(ctx: &mut NullWindowState, handle: dng_window_handle_v1, index: dng_u32) {
+    let slot = dng_handle_slot(handle);
+    if slot == ctx.free_slot {
+        ctx.free_slot = slot_mut(ctx, slot).link;
+    } else {
+        ctx.slot_count += 1;
+    }
+    let entry = slot_mut(ctx, slot);
+    entry.live = true;
+    entry.link = index;
+}
+
+// Retires the slot with a new generation (wrapping past 0) and pushes it on the free list.
+unsafe fn release_slot(ctx: &mut NullWindowState, handle: dng_window_handle_v1) {
+    let slot = dng_handle_slot(handle);
+    let free_slot = ctx.free_slot;
+    let entry = slot_mut(ctx, slot);
+    entry.generation = if entry.generation == dng_u32::MAX { 1 } else { entry.generation + 1 };
+    entry.live = false;
+    entry.link = free_slot;
+    ctx.free_slot = slot;
+}
+
+unsafe fn free_windows(ctx: &mut NullWindowState) {
+    let alloc = allocator(ctx);
+    for window in windows_mut(ctx) {
+        free_window_resources(alloc, window);
+    }
+    free_array(alloc, &mut ctx.windows, &mut ctx.window_capacity);
+    ctx.window_count = 0;
+    free_array(alloc, &mut ctx.slots, &mut ctx.slot_capacity);
+    ctx.slot_count = 0;
+    ctx.free_slot = NO_SLOT;
+}
+
+unsafe fn event_slot(ctx: &NullWindowState, offset: dng_u32) -> *mut dng_window_event_v1 {
+    ctx.events.add(((ctx.event_head + offset) % ctx.event_capacity) as usize)
+}
+
+// Maps host nanoseconds to engine ticks, rounding down; None before engine
+// zero or past u64::MAX ticks.
+fn engine_ticks(ctx: &NullWindowState, host_ns: dng_u64) -> Option<dng_u64> {
+    let elapsed = host_ns.checked_sub(ctx.time_zero_ns)?;
+    // elapsed * rate needs up to 128 bits; the quotient may still not fit.
+    let ticks = elapsed as u128 * ctx.ticks_per_second as u128 / NANOS_PER_SECOND as u128;
+    dng_u64::try_from(ticks).ok()
+}
+
+// Ensures room for `count` more events, so the pushes that follow cannot fail.
+unsafe fn reserve_events(ctx: &mut NullWindowState, count: dng_u32) -> dng_status_v1 {
+    let needed = ctx.event_count + count;
+    if needed <= ctx.event_capacity {
+        return DNG_STATUS_OK;
+    }
+    if needed > MAX_QUEUED_EVENTS {
+        return DNG_STATUS_OUT_OF_MEMORY;
+    }
+    let mut new_capacity = ctx.event_capacity.max(INITIAL_EVENT_CAPACITY);
+    while new_capacity < needed {
+        new_capacity *= 2;
+    }
+    let new_capacity = new_capacity.min(MAX_QUEUED_EVENTS);
+    let alloc = allocator(ctx);
+    let event_size = size_of::<dng_window_event_v1>() as dng_u64;
+    let event_align = align_of::<dng_window_event_v1>() as dng_u64;
+    let mem = alloc.alloc(event_size * new_capacity as dng_u64, event_align) as *mut dng_window_event_v1;
+    if mem.is_null() {
+        return DNG_STATUS_OUT_OF_MEMORY;
+    }
+    if !ctx.events.is_null() {
+        for i in 0..ctx.event_count {
+            ptr::write(mem.add(i as usize), ptr::read(event_slot(ctx, i)));
+        }
+        alloc.free(ctx.events as *mut c_void, event_size * ctx.event_capacity as dng_u64, event_align);
+    }
+    ctx.events = mem;
+    ctx.event_head = 0;
+    ctx.event_capacity = new_capacity;
+    DNG_STATUS_OK
+}
+
+// Stamps with the current host time in engine ticks, clamped to 0 before
+// engine zero and to u64::MAX past the tick range.
+unsafe fn push_event(ctx: &mut NullWindowState, mut event: dng_window_event_v1) -> dng_status_v1 {
+    event.timestamp = match engine_ticks(ctx, ctx.host_now_ns) {
+        Some(ticks) => ticks,
+        None if ctx.host_now_ns < ctx.time_zero_ns => 0,
+        None => dng_u64::MAX,
+    };
+    let reserve_status = reserve_events(ctx, 1);
+    if reserve_status != DNG_STATUS_OK {
+        return reserve_status;
+    }
+    ptr::write(event_slot(ctx, ctx.event_count), event);
+    ctx.event_count += 1;
+    DNG_STATUS_OK
+}
+
+unsafe fn pop_event(ctx: &mut NullWindowState) -> Option<dng_window_event_v1> {
+    if ctx.event_count == 0 {
+        return None;
+    }
+    let event = ptr::read(event_slot(ctx, 0));
+    ctx.event_head = (ctx.event_head + 1) % ctx.event_capacity;
+    ctx.event_count -= 1;
+    Some(event)
+}
+
+// Drops pending events targeting `handle`, keeping the rest in FIFO order.
+unsafe fn flush_window_events(ctx: &mut NullWindowState, handle: dng_window_handle_v1) {
+    let mut kept: dng_u32 = 0;
+    for i in 0..ctx.event_count {
+        let event = ptr::read(event_slot(ctx, i));
+        if event.handle != handle {
+            ptr::write(event_slot(ctx, kept), event);
+            kept += 1;
+        }
+    }
+    ctx.event_count = kept;
+}
+
+unsafe fn free_events(ctx: &mut NullWindowState) {
+    free_array(allocator(ctx), &mut ctx.events, &mut ctx.event_capacity);
+    ctx.event_head = 0;
+    ctx.event_count = 0;
+}
+
+unsafe fn clear_announcements(ctx: &mut NullWindowState) {
+    for i in 0..ctx.announcement_count as usize {
+        let record = &mut *ctx.announcements.add(i);
+        free_str(allocator(ctx), &mut record.text, &mut record.text_size);
+    }
+    ctx.announcement_count = 0;
+}
+
+unsafe fn free_announcements(ctx: &mut NullWindowState) {
+    clear_announcements(ctx);
+    free_array(allocator(ctx), &mut ctx.announcements, &mut ctx.announcement_capacity);
+}
+
+fn catch_unwind_status<F: FnOnce() -> dng_status_v1 + UnwindSafe>(f: F) -> dng_status_v1 {
+    match catch_unwind(f) {
+        Ok(s) => s,
+        Err(_) => DNG_STATUS_FAIL,
+    }
+}
+
+extern "C" fn window_create(raw_ctx: *mut c_void, desc: *const dng_window_desc_v1, out_handle: *mut dng_window_handle_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || desc.is_null() || out_handle.is_null() {
+            return DNG_STATUS_INVALID_ARG;
         }
-        let h = &*host;
-        if h.header.struct_size != size_of::<dng_host_api_v1>() as dng_u32 || h.header.abi_version != DNG_ABI_VERSION_V1 {
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let d = &*desc;
+        if d.flags & !DNG_WINDOW_FLAG_ALL != 0 {
             return DNG_STATUS_UNSUPPORTED;
         }
-        let alloc_fn = match (h.alloc, h.free) {
-            (Some(a), Some(_)) => a,
-            _ => return DNG_STATUS_INVALID_ARG,
-        };
-        let ctx_mem = alloc_fn(h.user, size_of::<NullWindowCtx>() as dng_u64, align_of::<NullWindowCtx>() as dng_u64);
-        if ctx_mem.is_null() {
-            return DNG_STATUS_OUT_OF_MEMORY;
-        }
-        let ctx = &mut *(ctx_mem as *mut NullWindowCtx);
-        ctx.host = host;
-        ctx.handle = 0;
-        ctx.size.width = 0;
-        ctx.size.height = 0;
-        ctx.title = ptr::null_mut();
-        ctx.title_size = 0;
-
-        let module_name_bytes: &[u8] = b"RustNullWindow";
-        let mut api = dng_module_api_v1 {
-            header: dng_abi_header_v1 { struct_size: size_of::<dng_module_api_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 },
-            module_name: dng_str_view_v1 { data: module_name_bytes.as_ptr() as *const c_char, size: module_name_bytes.len() as dng_u32 },
-            module_version_major: 1,
-            module_version_minor: 0,
-            module_version_patch: 0,
-            window: dng_window_api_v1 {
-                header: dng_abi_header_v1 { struct_size: size_of::<dng_window_api_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 },
-                ctx: ctx as *mut NullWindowCtx as *mut c_void,
-                create: Some(window_create),
-                destroy: Some(window_destroy),
-                poll: Some(window_poll),
-                get_size: Some(window_get_size),
-                set_title: Some(window_set_title),
-            },
-            shutdown: Some(module_shutdown),
+        let title_status = validate_title(d.title);
+        if title_status != DNG_STATUS_OK {
+            return title_status;
+        }
+        let handle = match reserve_window(ctx) {
+            Ok(h) => h,
+            Err(status) => return status,
+        };
+        let mut window = NullWindowRecord {
+            handle,
+            size: dng_window_size_v1 { width: d.width, height: d.height },
+            flags: d.flags,
+            parent: 0,
+            title: ptr::null_mut(),
+            title_size: 0,
+            frame_callback_thread: 0,
+            pointer_device_kind: DNG_PTR_MOUSE,
+            task_description: ptr::null_mut(),
+            task_description_size: 0,
+            dirty: EMPTY_RECT,
+            touch_to_mouse: false,
+            primary_touch_active: false,
+            primary_touch: 0,
+            aspect_numer: 0,
+            aspect_denom: 0,
         };
+        // A freshly allocated surface has never been presented.
+        window.dirty = full_rect(surface_size(&window));
+        let title_status = alloc_copy_title(allocator(ctx), &mut window, d.title);
+        if title_status != DNG_STATUS_OK {
+            return title_status;
+        }
+        claim_slot(ctx, handle, ctx.window_count);
+        ptr::write(ctx.windows.add(ctx.window_count as usize), window);
+        ctx.window_count += 1;
+        *out_handle = handle;
+        log_message(ctx.host, DNG_LOG_LEVEL_INFO, format_args!("window {} created ({}x{})", LogHandle(handle), d.width, d.height));
+        DNG_STATUS_OK
+    })
+}
 
-        ptr::write(out_api, api);
+extern "C" fn window_destroy(raw_ctx: *mut c_void, handle: dng_window_handle_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let index = match find_window(ctx, handle) {
+            Some(i) => i,
+            None => {
+                log_message(ctx.host, DNG_LOG_LEVEL_WARN, format_args!("destroy: invalid window handle {}", LogHandle(handle)));
+                return DNG_STATUS_INVALID_ARG;
+            }
+        };
+        let alloc = allocator(ctx);
+        let windows = windows_mut(ctx);
+        free_window_resources(alloc, &mut windows[index]);
+        let last = windows.len() - 1;
+        windows.swap(index, last);
+        let moved = windows[index].handle;
+        ctx.window_count -= 1;
+        if index != last {
+            slot_mut(ctx, dng_handle_slot(moved)).link = index as dng_u32;
+        }
+        release_slot(ctx, handle);
+        flush_window_events(ctx, handle);
+        if ctx.main_window == handle {
+            ctx.main_window = 0;
+        }
+        // Children of a destroyed window become top-level.
+        for window in windows_mut(ctx) {
+            if window.parent == handle {
+                window.parent = 0;
+            }
+        }
+        log_message(ctx.host, DNG_LOG_LEVEL_DEBUG, format_args!("window {} destroyed", LogHandle(handle)));
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_poll(raw_ctx: *mut c_void) -> dng_status_v1 {
+    catch_unwind_status(|| {
+        if raw_ctx.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_poll_event(raw_ctx: *mut c_void, out_event: *mut dng_window_event_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_event.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        match pop_event(ctx) {
+            Some(event) => {
+                ptr::write(out_event, event);
+                DNG_STATUS_OK
+            }
+            None => DNG_STATUS_EMPTY,
+        }
+    })
+}
+
+extern "C" fn window_set_touch_to_mouse_emulation(raw_ctx: *mut c_void, handle: dng_window_handle_v1, enabled: dng_bool_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        window.touch_to_mouse = enabled != DNG_BOOL_FALSE;
+        // A touch that began before a toggle is never mirrored.
+        window.primary_touch_active = false;
+        DNG_STATUS_OK
+    })
+}
+
+// Maps a touch event to its emulated mouse event. Only the first finger down
+// drives the mouse; further fingers are ignored until it lifts. The window is
+// not modified; track_primary_touch commits the result once it is queued.
+fn emulate_mouse(window: &NullWindowRecord, touch: &dng_window_event_v1) -> Option<dng_window_event_v1> {
+    if !window.touch_to_mouse {
+        return None;
+    }
+    let pointer = unsafe { touch.payload.pointer };
+    let is_primary = window.primary_touch_active && window.primary_touch == pointer.touch_id;
+    let mouse_type = match touch.event_type {
+        DNG_EVENT_TOUCH_DOWN if !window.primary_touch_active => DNG_EVENT_MOUSE_DOWN,
+        DNG_EVENT_TOUCH_MOVE if is_primary => DNG_EVENT_MOUSE_MOVE,
+        DNG_EVENT_TOUCH_UP if is_primary => DNG_EVENT_MOUSE_UP,
+        _ => return None,
+    };
+    let mouse = dng_window_pointer_v1 { x: pointer.x, y: pointer.y, touch_id: 0 };
+    Some(dng_window_event_v1 { event_type: mouse_type, handle: touch.handle, timestamp: 0, payload: dng_window_event_payload_v1 { pointer: mouse } })
+}
+
+fn track_primary_touch(window: &mut NullWindowRecord, touch: &dng_window_event_v1, mouse: &dng_window_event_v1) {
+    match mouse.event_type {
+        DNG_EVENT_MOUSE_DOWN => {
+            window.primary_touch_active = true;
+            window.primary_touch = unsafe { touch.payload.pointer.touch_id };
+        }
+        DNG_EVENT_MOUSE_UP => window.primary_touch_active = false,
+        _ => {}
+    }
+}
+
+// Stores the hint as given; 0/0 clears it and a single zero term is rejected.
+extern "C" fn window_set_aspect_hint(raw_ctx: *mut c_void, handle: dng_window_handle_v1, numer: dng_u32, denom: dng_u32) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 || ((numer == 0) != (denom == 0)) {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        window.aspect_numer = numer;
+        window.aspect_denom = denom;
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_get_aspect_hint(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_numer: *mut dng_u32, out_denom: *mut dng_u32) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_numer.is_null() || out_denom.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        ptr::write(out_numer, window.aspect_numer);
+        ptr::write(out_denom, window.aspect_denom);
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_get_is_main_window(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_is_main: *mut dng_bool_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_is_main.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if window_mut(ctx, handle).is_none() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        ptr::write(out_is_main, if ctx.main_window == handle { DNG_BOOL_TRUE } else { DNG_BOOL_FALSE });
+        DNG_STATUS_OK
+    })
+}
+
+// At most one main window: setting one demotes the previous main window, and
+// clearing a window that is not the main window leaves the current one alone.
+extern "C" fn window_set_is_main_window(raw_ctx: *mut c_void, handle: dng_window_handle_v1, is_main: dng_bool_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if window_mut(ctx, handle).is_none() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        if is_main != DNG_BOOL_FALSE {
+            ctx.main_window = handle;
+        } else if ctx.main_window == handle {
+            ctx.main_window = 0;
+        }
+        DNG_STATUS_OK
+    })
+}
+
+// No renderer ever attaches to a null window.
+extern "C" fn window_get_renderer_info(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_info: *mut dng_renderer_info_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_info.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        if find_window(&mut state, handle).is_none() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        ptr::write(out_info, dng_renderer_info_v1 { kind: DNG_RENDERER_NONE, api_version: 0, vendor_id: 0, device_id: 0 });
+        DNG_STATUS_OK
+    })
+}
+
+// Events are stamped when queued; ones already queued keep their old stamp.
+extern "C" fn window_set_time_base(raw_ctx: *mut c_void, host_time_at_engine_zero: dng_u64, engine_ticks_per_second: dng_u64) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || engine_ticks_per_second == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        ctx.time_zero_ns = host_time_at_engine_zero;
+        ctx.ticks_per_second = engine_ticks_per_second;
+        DNG_STATUS_OK
+    })
+}
+
+// Same mapping events are stamped with. Host times before engine zero and
+// results past u64::MAX are rejected with INVALID_ARG.
+extern "C" fn window_convert_time(raw_ctx: *mut c_void, host_ns: dng_u64, out_engine_ticks: *mut dng_u64) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_engine_ticks.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        match engine_ticks(&state, host_ns) {
+            Some(t) => {
+                ptr::write(out_engine_ticks, t);
+                DNG_STATUS_OK
+            }
+            None => DNG_STATUS_INVALID_ARG,
+        }
+    })
+}
+
+// Raises the sticky quit flag; DNG_EVENT_QUIT_REQUESTED is queued only when the
+// flag goes from clear to set, so repeated requests do not flood the queue.
+extern "C" fn window_request_quit(raw_ctx: *mut c_void) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if ctx.quit_requested {
+            return DNG_STATUS_OK;
+        }
+        let event = dng_window_event_v1 { event_type: DNG_EVENT_QUIT_REQUESTED, handle: 0, timestamp: 0, payload: dng_window_event_payload_v1 { reserved: [0; 4] } };
+        let push_status = push_event(ctx, event);
+        if push_status != DNG_STATUS_OK {
+            return push_status;
+        }
+        ctx.quit_requested = true;
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_get_quit_requested(raw_ctx: *mut c_void, out_requested: *mut dng_bool_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_requested.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &*state;
+        ptr::write(out_requested, if ctx.quit_requested { DNG_BOOL_TRUE } else { DNG_BOOL_FALSE });
+        DNG_STATUS_OK
+    })
+}
+
+// Clears the quit flag; an already queued DNG_EVENT_QUIT_REQUESTED stays queued.
+extern "C" fn window_cancel_quit(raw_ctx: *mut c_void) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        ctx.quit_requested = false;
+        DNG_STATUS_OK
+    })
+}
+
+// Accumulates `rect`, clipped to the window surface, into the dirty region.
+extern "C" fn window_mark_dirty(raw_ctx: *mut c_void, handle: dng_window_handle_v1, rect: dng_rect_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        window.dirty = union_rect(window.dirty, clamp_rect(rect, surface_size(window)));
         DNG_STATUS_OK
     })
 }
+
+// Returns the accumulated dirty region and resets it; DNG_STATUS_EMPTY (with a
+// zeroed rect) when nothing changed since the last call.
+extern "C" fn window_get_and_clear_dirty(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_rect: *mut dng_rect_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_rect.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        let dirty = window.dirty;
+        window.dirty = EMPTY_RECT;
+        ptr::write(out_rect, dirty);
+        if rect_is_empty(dirty) {
+            DNG_STATUS_EMPTY
+        } else {
+            DNG_STATUS_OK
+        }
+    })
+}
+
+// Queues a DNG_EVENT_IPC event for `dst` carrying a copy of the payload.
+extern "C" fn window_send_ipc_message(
+    raw_ctx: *mut c_void,
+    src: dng_window_handle_v1,
+    dst: dng_window_handle_v1,
+    kind: dng_u32,
+    payload: *const c_void,
+    size: dng_u32,
+) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || src == 0 || dst == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        if size > DNG_IPC_PAYLOAD_MAX || (size > 0 && payload.is_null()) {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if find_window(ctx, src).is_none() || find_window(ctx, dst).is_none() {
+            return DNG_STATUS_NOT_FOUND;
+        }
+        let mut ipc = dng_window_ipc_v1 { source: src, kind, size, data: [0; DNG_IPC_PAYLOAD_MAX as usize] };
+        if size > 0 {
+            ptr::copy_nonoverlapping(payload as *const dng_u8, ipc.data.as_mut_ptr(), size as usize);
+        }
+        push_event(ctx, dng_window_event_v1 { event_type: DNG_EVENT_IPC, handle: dst, timestamp: 0, payload: dng_window_event_payload_v1 { ipc } })
+    })
+}
+
+extern "C" fn window_get_size(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_size: *mut dng_window_size_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_size.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        ptr::write(out_size, surface_size(window));
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_set_size(raw_ctx: *mut c_void, handle: dng_window_handle_v1, size: *const dng_window_size_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || size.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let s = ptr::read(size);
+        if s.width == 0 || s.height == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        window.size = s;
+        // Resizing reallocates the surface, so all of it needs a redraw.
+        window.dirty = full_rect(surface_size(window));
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_set_title(raw_ctx: *mut c_void, handle: dng_window_handle_v1, title: dng_str_view_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let alloc = allocator(ctx);
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        let title_status = validate_title(title);
+        if title_status != DNG_STATUS_OK {
+            return title_status;
+        }
+        let copy_status = replace_str(alloc, title, &mut window.title, &mut window.title_size);
+        if copy_status == DNG_STATUS_OK {
+            log_message(alloc.host, DNG_LOG_LEVEL_DEBUG, format_args!("window {} title set ({} bytes)", LogHandle(handle), title.size));
+        }
+        copy_status
+    })
+}
+
+extern "C" fn window_set_frame_callback_thread(raw_ctx: *mut c_void, handle: dng_window_handle_v1, thread_id: dng_u64) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        window.frame_callback_thread = thread_id;
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_get_frame_callback_thread(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_thread_id: *mut dng_u64) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_thread_id.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        ptr::write(out_thread_id, window.frame_callback_thread);
+        DNG_STATUS_OK
+    })
+}
+
+// Copies a stored string (no terminator) and always reports its byte size.
+// A NULL buffer is a pure size query; a too-small buffer is left untouched.
+unsafe fn copy_out_str(data: *const c_char, size: dng_u32, buffer: *mut c_char, capacity: dng_u32, out_size: *mut dng_u32) -> dng_status_v1 {
+    ptr::write(out_size, size);
+    if buffer.is_null() {
+        return DNG_STATUS_OK;
+    }
+    if capacity < size {
+        return DNG_STATUS_INVALID_ARG;
+    }
+    if size > 0 {
+        ptr::copy_nonoverlapping(data, buffer, size as usize);
+    }
+    DNG_STATUS_OK
+}
+
+extern "C" fn window_get_title(
+    raw_ctx: *mut c_void,
+    handle: dng_window_handle_v1,
+    buffer: *mut c_char,
+    capacity: dng_u32,
+    out_size: *mut dng_u32,
+) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_size.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        copy_out_str(window.title, window.title_size, buffer, capacity, out_size)
+    })
+}
+
+extern "C" fn window_set_task_description(raw_ctx: *mut c_void, handle: dng_window_handle_v1, desc: dng_str_view_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let alloc = allocator(ctx);
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        let desc_status = validate_title(desc);
+        if desc_status != DNG_STATUS_OK {
+            return desc_status;
+        }
+        replace_str(alloc, desc, &mut window.task_description, &mut window.task_description_size)
+    })
+}
+
+extern "C" fn window_get_task_description(
+    raw_ctx: *mut c_void,
+    handle: dng_window_handle_v1,
+    buffer: *mut c_char,
+    capacity: dng_u32,
+    out_size: *mut dng_u32,
+) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_size.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        copy_out_str(window.task_description, window.task_description_size, buffer, capacity, out_size)
+    })
+}
+
+extern "C" fn window_get_flags(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_flags: *mut dng_u32) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_flags.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        ptr::write(out_flags, window.flags);
+        DNG_STATUS_OK
+    })
+}
+
+// Parent 0 makes the window top-level; a parent that would create a cycle is rejected.
+extern "C" fn window_set_parent(raw_ctx: *mut c_void, handle: dng_window_handle_v1, parent: dng_window_handle_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if find_window(ctx, handle).is_none() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        if parent != 0 {
+            if find_window(ctx, parent).is_none() {
+                return DNG_STATUS_INVALID_ARG;
+            }
+            let mut ancestor = parent;
+            while ancestor != 0 {
+                if ancestor == handle {
+                    return DNG_STATUS_INVALID_ARG;
+                }
+                ancestor = parent_of(ctx, ancestor);
+            }
+        }
+        if let Some(window) = window_mut(ctx, handle) {
+            window.parent = parent;
+        }
+        DNG_STATUS_OK
+    })
+}
+
+// Writes ancestors nearest-first and always reports how many there are.
+// A NULL buffer is a pure depth query; a too-small buffer is left untouched.
+extern "C" fn window_get_ancestor_chain(
+    raw_ctx: *mut c_void,
+    handle: dng_window_handle_v1,
+    buffer: *mut dng_window_handle_v1,
+    max_depth: dng_u32,
+    out_depth: *mut dng_u32,
+) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_depth.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if find_window(ctx, handle).is_none() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let depth = window_depth(ctx, handle);
+        ptr::write(out_depth, depth);
+        if buffer.is_null() {
+            return DNG_STATUS_OK;
+        }
+        if max_depth < depth {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let mut ancestor = parent_of(ctx, handle);
+        for i in 0..depth as usize {
+            ptr::write(buffer.add(i), ancestor);
+            ancestor = parent_of(ctx, ancestor);
+        }
+        DNG_STATUS_OK
+    })
+}
+
+// Depth 0 is a top-level window.
+extern "C" fn window_get_depth(raw_ctx: *mut c_void, handle: dng_window_handle_v1, out_depth: *mut dng_u32) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_depth.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if find_window(ctx, handle).is_none() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        ptr::write(out_depth, window_depth(ctx, handle));
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_get_pointer_device_kind(
+    raw_ctx: *mut c_void,
+    handle: dng_window_handle_v1,
+    out_kind: *mut dng_pointer_device_kind_v1,
+) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_kind.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        ptr::write(out_kind, window.pointer_device_kind);
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn window_get_media_key_state(raw_ctx: *mut c_void, out_keys: *mut dng_u32) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_keys.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &*state;
+        ptr::write(out_keys, ctx.media_keys);
+        DNG_STATUS_OK
+    })
+}
+
+// Null backend: announcements are only recorded for the test-only
+// dngNullWindowGetAnnouncement_v1 query; nothing is spoken.
+extern "C" fn window_announce(
+    raw_ctx: *mut c_void,
+    handle: dng_window_handle_v1,
+    text: dng_str_view_v1,
+    priority: dng_announce_priority_v1,
+) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        if priority != DNG_ANNOUNCE_POLITE && priority != DNG_ANNOUNCE_ASSERTIVE {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        // Announcement text follows the title rules (UTF-8, 64 KiB cap).
+        let text_status = validate_title(text);
+        if text_status != DNG_STATUS_OK {
+            return text_status;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if find_window(ctx, handle).is_none() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let reserve_status = grow_array(
+            allocator(ctx),
+            &mut ctx.announcements,
+            ctx.announcement_count,
+            &mut ctx.announcement_capacity,
+            INITIAL_ANNOUNCEMENT_CAPACITY,
+            MAX_ANNOUNCEMENTS,
+        );
+        if reserve_status != DNG_STATUS_OK {
+            return reserve_status;
+        }
+        let mut record = NullAnnouncementRecord { handle, priority, text: ptr::null_mut(), text_size: 0 };
+        let copy_status = alloc_copy_str(allocator(ctx), text, &mut record.text, &mut record.text_size);
+        if copy_status != DNG_STATUS_OK {
+            return copy_status;
+        }
+        ptr::write(ctx.announcements.add(ctx.announcement_count as usize), record);
+        ctx.announcement_count += 1;
+        DNG_STATUS_OK
+    })
+}
+
+unsafe fn audio_devices_mut(ctx: &mut NullWindowState) -> &mut [NullAudioDevice] {
+    if ctx.audio_devices.is_null() {
+        return &mut [];
+    }
+    core::slice::from_raw_parts_mut(ctx.audio_devices, ctx.audio_device_count as usize)
+}
+
+unsafe fn audio_device_mut(ctx: &mut NullWindowState, device: dng_audio_device_handle_v1) -> Option<&mut NullAudioDevice> {
+    if device == 0 {
+        return None;
+    }
+    audio_devices_mut(ctx).iter_mut().find(|d| d.handle == device)
+}
+
+unsafe fn free_audio_devices(ctx: &mut NullWindowState) {
+    free_array(allocator(ctx), &mut ctx.audio_devices, &mut ctx.audio_device_capacity);
+    ctx.audio_device_count = 0;
+}
+
+extern "C" fn audio_open_device(raw_ctx: *mut c_void, sample_rate: dng_u32, channels: dng_u32, out_device: *mut dng_audio_device_handle_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_device.is_null() || sample_rate == 0 || channels == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        if channels > MAX_AUDIO_CHANNELS {
+            return DNG_STATUS_UNSUPPORTED;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let reserve_status = grow_array(
+            allocator(ctx),
+            &mut ctx.audio_devices,
+            ctx.audio_device_count,
+            &mut ctx.audio_device_capacity,
+            INITIAL_AUDIO_DEVICE_CAPACITY,
+            MAX_AUDIO_DEVICES,
+        );
+        if reserve_status != DNG_STATUS_OK {
+            return reserve_status;
+        }
+        let device = NullAudioDevice { handle: ctx.next_audio_device, sample_rate, channels, frames_submitted: 0 };
+        ctx.next_audio_device += 1;
+        *out_device = device.handle;
+        ptr::write(ctx.audio_devices.add(ctx.audio_device_count as usize), device);
+        ctx.audio_device_count += 1;
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn audio_close_device(raw_ctx: *mut c_void, device: dng_audio_device_handle_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || device == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let devices = audio_devices_mut(ctx);
+        let index = match devices.iter().position(|d| d.handle == device) {
+            Some(i) => i,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        let last = devices.len() - 1;
+        devices.swap(index, last);
+        ctx.audio_device_count -= 1;
+        DNG_STATUS_OK
+    })
+}
+
+// Accepts and discards the samples; only the frame count is kept. The rate
+// must match the device because the null backend never resamples.
+extern "C" fn audio_submit_buffer(
+    raw_ctx: *mut c_void,
+    device: dng_audio_device_handle_v1,
+    samples: *const f32,
+    frame_count: dng_u32,
+    sample_rate: dng_u32,
+) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || (frame_count > 0 && samples.is_null()) {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let d = match audio_device_mut(ctx, device) {
+            Some(d) => d,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        if sample_rate != d.sample_rate {
+            return DNG_STATUS_UNSUPPORTED;
+        }
+        d.frames_submitted += frame_count as dng_u64;
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn audio_get_latency(raw_ctx: *mut c_void, device: dng_audio_device_handle_v1, out_latency_us: *mut dng_u64) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_latency_us.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        if audio_device_mut(&mut state, device).is_none() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        ptr::write(out_latency_us, NULL_AUDIO_LATENCY_US);
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn audio_get_submitted_frames(raw_ctx: *mut c_void, device: dng_audio_device_handle_v1, out_frames: *mut dng_u64) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_frames.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let d = match audio_device_mut(&mut state, device) {
+            Some(d) => d,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        ptr::write(out_frames, d.frames_submitted);
+        DNG_STATUS_OK
+    })
+}
+
+extern "C" fn module_shutdown(raw_ctx: *mut c_void, host: *const dng_host_api_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || host.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let null_ctx = &*(raw_ctx as *const NullWindowCtx);
+        {
+            let mut state = lock_state(null_ctx);
+            if state.uses_host_alloc && (*host).free.is_none() {
+                return DNG_STATUS_INVALID_ARG;
+            }
+            state.closing = true;
+        }
+        // Calls that already entered the module, including ones blocked on the
+        // lock, see the closing flag and leave. in_flight is re-checked under
+        // the lock so no caller is left holding it when the context is freed.
+        let deadline = Instant::now() + SHUTDOWN_DRAIN_TIMEOUT;
+        let uses_host_alloc = loop {
+            let mut state = lock_state(null_ctx);
+            if null_ctx.in_flight.load(Ordering::Acquire) != 0 {
+                drop(state);
+                if Instant::now() >= deadline {
+                    // The context stays alive and closed; shutdown may be retried.
+                    return DNG_STATUS_FAIL;
+                }
+                std::thread::sleep(SHUTDOWN_DRAIN_POLL);
+                continue;
+            }
+            let ctx = &mut *state;
+            log_message(host, DNG_LOG_LEVEL_INFO, format_args!("shutdown: releasing {} live windows", ctx.window_count));
+            free_windows(ctx);
+            free_events(ctx);
+            free_announcements(ctx);
+            free_audio_devices(ctx);
+            break ctx.uses_host_alloc;
+        };
+        ptr::drop_in_place(raw_ctx as *mut NullWindowCtx);
+        // The context itself came from the same allocator as everything it owns.
+        let alloc = NullAllocator { host, uses_host_alloc };
+        alloc.free(raw_ctx, size_of::<NullWindowCtx>() as dng_u64, align_of::<NullWindowCtx>() as dng_u64);
+        DNG_STATUS_OK
+    })
+}
+
+// Checked payload accessors: each copies the payload only when the event type
+// matches, so hosts never read the wrong union member.
+fn get_event_payload<T: Copy + RefUnwindSafe>(
+    event: *const dng_window_event_v1,
+    out: *mut T,
+    accepted: &'static [dng_window_event_type_v1],
+    read: unsafe fn(&dng_window_event_payload_v1) -> T,
+) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if event.is_null() || out.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let e = &*event;
+        if !accepted.contains(&e.event_type) {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        ptr::write(out, read(&e.payload));
+        DNG_STATUS_OK
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn dngEventGetResize(event: *const dng_window_event_v1, out_size: *mut dng_window_size_v1) -> dng_status_v1 {
+    get_event_payload(event, out_size, &[DNG_EVENT_RESIZE], |p| unsafe { p.size })
+}
+
+#[no_mangle]
+pub extern "C" fn dngEventGetFocus(event: *const dng_window_event_v1, out_focus: *mut dng_window_focus_v1) -> dng_status_v1 {
+    get_event_payload(event, out_focus, &[DNG_EVENT_FOCUS], |p| unsafe { p.focus })
+}
+
+#[no_mangle]
+pub extern "C" fn dngEventGetIpc(event: *const dng_window_event_v1, out_ipc: *mut dng_window_ipc_v1) -> dng_status_v1 {
+    get_event_payload(event, out_ipc, &[DNG_EVENT_IPC], |p| unsafe { p.ipc })
+}
+
+// Accepts every DNG_EVENT_TOUCH_* and DNG_EVENT_MOUSE_* type.
+#[no_mangle]
+pub extern "C" fn dngEventGetPointer(event: *const dng_window_event_v1, out_pointer: *mut dng_window_pointer_v1) -> dng_status_v1 {
+    const POINTER_EVENTS: [dng_window_event_type_v1; 6] = [
+        DNG_EVENT_TOUCH_DOWN,
+        DNG_EVENT_TOUCH_MOVE,
+        DNG_EVENT_TOUCH_UP,
+        DNG_EVENT_MOUSE_DOWN,
+        DNG_EVENT_MOUSE_MOVE,
+        DNG_EVENT_MOUSE_UP,
+    ];
+    get_event_payload(event, out_pointer, &POINTER_EVENTS, |p| unsafe { p.pointer })
+}
+
+#[no_mangle]
+pub extern "C" fn dngModuleQueryInfo(out_info: *mut dng_module_info) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if out_info.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let info = dng_module_info {
+            header: dng_abi_header_v1 { struct_size: size_of::<dng_module_info>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 },
+            module_name: dng_str_view_v1 { data: MODULE_NAME.as_ptr() as *const c_char, size: MODULE_NAME.len() as dng_u32 },
+            module_version_major: MODULE_VERSION_MAJOR,
+            module_version_minor: MODULE_VERSION_MINOR,
+            module_version_patch: MODULE_VERSION_PATCH,
+            min_abi_version: DNG_ABI_VERSION_V1,
+            max_abi_version: DNG_ABI_VERSION_V2,
+            api_families: DNG_API_FAMILY_WINDOW | DNG_API_FAMILY_AUDIO,
+        };
+        ptr::write(out_info, info);
+        DNG_STATUS_OK
+    })
+}
+
+// Events and audio are only reachable through the v2 tables.
+pub fn abi_struct_sizes_v1() -> dng_abi_struct_sizes_v1 {
+    dng_abi_struct_sizes_v1 {
+        header: dng_abi_header_v1 { struct_size: size_of::<dng_abi_struct_sizes_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 },
+        host_api: size_of::<dng_host_api_v1>() as dng_u32,
+        module_api: size_of::<dng_module_api_v1>() as dng_u32,
+        window_api: size_of::<dng_window_api_v1>() as dng_u32,
+        window_desc: size_of::<dng_window_desc_v1>() as dng_u32,
+        window_event: 0,
+        module_info: size_of::<dng_module_info>() as dng_u32,
+        audio_api: 0,
+    }
+}
+
+pub fn abi_struct_sizes_v2() -> dng_abi_struct_sizes_v1 {
+    dng_abi_struct_sizes_v1 {
+        header: dng_abi_header_v1 { struct_size: size_of::<dng_abi_struct_sizes_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V2 },
+        host_api: size_of::<dng_host_api_v1>() as dng_u32,
+        module_api: size_of::<dng_module_api_v2>() as dng_u32,
+        window_api: size_of::<dng_window_api_v2>() as dng_u32,
+        window_desc: size_of::<dng_window_desc_v1>() as dng_u32,
+        window_event: size_of::<dng_window_event_v1>() as dng_u32,
+        module_info: size_of::<dng_module_info>() as dng_u32,
+        audio_api: size_of::<dng_audio_api_v1>() as dng_u32,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn dngModuleQueryStructSizes(abi_version: dng_u32, out_sizes: *mut dng_abi_struct_sizes_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if out_sizes.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let sizes = match abi_version {
+            DNG_ABI_VERSION_V1 => abi_struct_sizes_v1(),
+            DNG_ABI_VERSION_V2 => abi_struct_sizes_v2(),
+            _ => return DNG_STATUS_UNSUPPORTED,
+        };
+        ptr::write(out_sizes, sizes);
+        DNG_STATUS_OK
+    })
+}
+
+// Validates the host API and allocates a fresh module context.
+unsafe fn create_module_ctx(host: *const dng_host_api_v1) -> Result<*mut c_void, dng_status_v1> {
+    let h = &*host;
+    // Larger host structs are accepted; trailing extension fields are ignored.
+    if h.header.struct_size < size_of::<dng_host_api_v1>() as dng_u32 || h.header.abi_version != DNG_ABI_VERSION_V1 {
+        return Err(DNG_STATUS_UNSUPPORTED);
+    }
+    // Either both callbacks are provided or neither; a lone alloc or free is a host bug.
+    let uses_host_alloc = match (h.alloc, h.free) {
+        (Some(_), Some(_)) => true,
+        (None, None) => false,
+        _ => return Err(DNG_STATUS_INVALID_ARG),
+    };
+    let alloc = NullAllocator { host, uses_host_alloc };
+    let ctx_mem = alloc.alloc(size_of::<NullWindowCtx>() as dng_u64, align_of::<NullWindowCtx>() as dng_u64);
+    if ctx_mem.is_null() {
+        return Err(DNG_STATUS_OUT_OF_MEMORY);
+    }
+    let state = NullWindowState {
+        host,
+        uses_host_alloc,
+        windows: ptr::null_mut(),
+        window_count: 0,
+        window_capacity: 0,
+        slots: ptr::null_mut(),
+        slot_count: 0,
+        slot_capacity: 0,
+        free_slot: NO_SLOT,
+        media_keys: 0,
+        events: ptr::null_mut(),
+        event_head: 0,
+        event_count: 0,
+        event_capacity: 0,
+        announcements: ptr::null_mut(),
+        announcement_count: 0,
+        announcement_capacity: 0,
+        audio_devices: ptr::null_mut(),
+        audio_device_count: 0,
+        audio_device_capacity: 0,
+        next_audio_device: 1,
+        quit_requested: false,
+        main_window: 0,
+        time_zero_ns: 0,
+        ticks_per_second: NANOS_PER_SECOND,
+        host_now_ns: 0,
+        closing: false,
+    };
+    ptr::write(ctx_mem as *mut NullWindowCtx, NullWindowCtx { state: Mutex::new(state), in_flight: AtomicU32::new(0) });
+    Ok(ctx_mem)
+}
+
+// The host declares the size of every table it hands in (DngAbi.h); the module
+// never writes past a declared size, and a table smaller than the version's
+// layout is rejected instead of being filled partially.
+unsafe fn declares_at_least<T>(header: *const dng_abi_header_v1) -> bool {
+    ptr::read(header).struct_size as usize >= size_of::<T>()
+}
+
+fn table_header<T>(abi_version: dng_u32) -> dng_abi_header_v1 {
+    dng_abi_header_v1 { struct_size: size_of::<T>() as dng_u32, abi_version }
+}
+
+fn module_name() -> dng_str_view_v1 {
+    dng_str_view_v1 { data: MODULE_NAME.as_ptr() as *const c_char, size: MODULE_NAME.len() as dng_u32 }
+}
+
+fn window_api_v2(ctx: *mut c_void) -> dng_window_api_v2 {
+    dng_window_api_v2 {
+        header: table_header::<dng_window_api_v2>(DNG_ABI_VERSION_V2),
+        ctx,
+        create: Some(window_create),
+        destroy: Some(window_destroy),
+        poll: Some(window_poll),
+        get_size: Some(window_get_size),
+        set_title: Some(window_set_title),
+        set_frame_callback_thread: Some(window_set_frame_callback_thread),
+        get_frame_callback_thread: Some(window_get_frame_callback_thread),
+        get_title: Some(window_get_title),
+        get_pointer_device_kind: Some(window_get_pointer_device_kind),
+        set_size: Some(window_set_size),
+        get_media_key_state: Some(window_get_media_key_state),
+        poll_event: Some(window_poll_event),
+        announce: Some(window_announce),
+        set_window_task_description: Some(window_set_task_description),
+        get_window_task_description: Some(window_get_task_description),
+        get_flags: Some(window_get_flags),
+        set_parent: Some(window_set_parent),
+        get_ancestor_chain: Some(window_get_ancestor_chain),
+        get_window_depth: Some(window_get_depth),
+        send_ipc_message: Some(window_send_ipc_message),
+        mark_dirty: Some(window_mark_dirty),
+        get_and_clear_dirty: Some(window_get_and_clear_dirty),
+        request_quit: Some(window_request_quit),
+        get_quit_requested: Some(window_get_quit_requested),
+        cancel_quit: Some(window_cancel_quit),
+        set_touch_to_mouse_emulation: Some(window_set_touch_to_mouse_emulation),
+        set_time_base: Some(window_set_time_base),
+        convert_time: Some(window_convert_time),
+        get_renderer_info: Some(window_get_renderer_info),
+        set_window_aspect_hint: Some(window_set_aspect_hint),
+        get_window_aspect_hint: Some(window_get_aspect_hint),
+        get_is_main_window: Some(window_get_is_main_window),
+        set_is_main_window: Some(window_set_is_main_window),
+    }
+}
+
+fn audio_api_v1(ctx: *mut c_void) -> dng_audio_api_v1 {
+    dng_audio_api_v1 {
+        header: table_header::<dng_audio_api_v1>(DNG_ABI_VERSION_V1),
+        ctx,
+        open_device: Some(audio_open_device),
+        close_device: Some(audio_close_device),
+        submit_buffer: Some(audio_submit_buffer),
+        get_latency: Some(audio_get_latency),
+        get_submitted_frames: Some(audio_get_submitted_frames),
+    }
+}
+
+// Fills the frozen v1 table; extensions are only available through dngModuleGetApi_v2.
+#[no_mangle]
+pub extern "C" fn dngModuleGetApi_v1(host: *const dng_host_api_v1, out_api: *mut dng_module_api_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if host.is_null() || out_api.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        if !declares_at_least::<dng_module_api_v1>(ptr::addr_of!((*out_api).header))
+            || !declares_at_least::<dng_window_api_v1>(ptr::addr_of!((*out_api).window.header))
+        {
+            return DNG_STATUS_UNSUPPORTED;
+        }
+        let ctx_mem = match create_module_ctx(host) {
+            Ok(ctx) => ctx,
+            Err(status) => return status,
+        };
+        let api = dng_module_api_v1 {
+            header: table_header::<dng_module_api_v1>(DNG_ABI_VERSION_V1),
+            module_name: module_name(),
+            module_version_major: MODULE_VERSION_MAJOR,
+            module_version_minor: MODULE_VERSION_MINOR,
+            module_version_patch: MODULE_VERSION_PATCH,
+            window: dng_window_api_v1 {
+                header: table_header::<dng_window_api_v1>(DNG_ABI_VERSION_V1),
+                ctx: ctx_mem,
+                create: Some(window_create),
+                destroy: Some(window_destroy),
+                poll: Some(window_poll),
+                get_size: Some(window_get_size),
+                set_title: Some(window_set_title),
+            },
+            shutdown: Some(module_shutdown),
+        };
+        ptr::write(out_api, api);
+        DNG_STATUS_OK
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn dngModuleGetApi_v2(host: *const dng_host_api_v1, out_api: *mut dng_module_api_v2) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if host.is_null() || out_api.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        if !declares_at_least::<dng_module_api_v2>(ptr::addr_of!((*out_api).header))
+            || !declares_at_least::<dng_window_api_v2>(ptr::addr_of!((*out_api).window.header))
+            || !declares_at_least::<dng_audio_api_v1>(ptr::addr_of!((*out_api).audio.header))
+        {
+            return DNG_STATUS_UNSUPPORTED;
+        }
+        let ctx_mem = match create_module_ctx(host) {
+            Ok(ctx) => ctx,
+            Err(status) => return status,
+        };
+        let api = dng_module_api_v2 {
+            header: table_header::<dng_module_api_v2>(DNG_ABI_VERSION_V2),
+            module_name: module_name(),
+            module_version_major: MODULE_VERSION_MAJOR,
+            module_version_minor: MODULE_VERSION_MINOR,
+            module_version_patch: MODULE_VERSION_PATCH,
+            window: window_api_v2(ctx_mem),
+            shutdown: Some(module_shutdown),
+            audio: audio_api_v1(ctx_mem),
+        };
+        ptr::write(out_api, api);
+        DNG_STATUS_OK
+    })
+}
+
+// Test-only injection: simulates pointer input of the given device kind on a
+// window. `ctx` is the window API ctx returned by dngModuleGetApi_v1.
+#[no_mangle]
+pub extern "C" fn dngNullWindowInjectPointerInput_v1(raw_ctx: *mut c_void, handle: dng_window_handle_v1, kind: dng_pointer_device_kind_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || handle == 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        if kind != DNG_PTR_MOUSE && kind != DNG_PTR_TOUCH && kind != DNG_PTR_STYLUS {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let window = match window_mut(ctx, handle) {
+            Some(w) => w,
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        window.pointer_device_kind = kind;
+        DNG_STATUS_OK
+    })
+}
+
+// Test-only injection: presses or releases one DNG_MEDIA_KEY_* key.
+#[no_mangle]
+pub extern "C" fn dngNullWindowInjectMediaKey_v1(raw_ctx: *mut c_void, key: dng_media_key_v1, pressed: dng_bool_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || key.count_ones() != 1 || key & !DNG_MEDIA_KEY_ALL != 0 {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        if pressed != DNG_BOOL_FALSE {
+            ctx.media_keys |= key;
+        } else {
+            ctx.media_keys &= !key;
+        }
+        DNG_STATUS_OK
+    })
+}
+
+// Test-only injection: enqueues a copy of `event` for a live window so the
+// host can drain it through poll_event. The event's timestamp is taken as the
+// current host time in nanoseconds and replaced by its engine-tick stamp.
+// Window state is not modified, except that touch events are followed by
+// their emulated mouse event when touch-to-mouse emulation is enabled. Room
+// for both events is reserved first, so a full queue changes nothing.
+#[no_mangle]
+pub extern "C" fn dngNullWindowInjectEvent_v1(raw_ctx: *mut c_void, event: *const dng_window_event_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || event.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let e = ptr::read(event);
+        if !matches!(e.event_type, DNG_EVENT_RESIZE | DNG_EVENT_CLOSE | DNG_EVENT_FOCUS | DNG_EVENT_TOUCH_DOWN..=DNG_EVENT_MOUSE_UP) {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &mut *state;
+        let mouse = match window_mut(ctx, e.handle) {
+            Some(window) => emulate_mouse(window, &e),
+            None => return DNG_STATUS_INVALID_ARG,
+        };
+        let reserve_status = reserve_events(ctx, if mouse.is_some() { 2 } else { 1 });
+        if reserve_status != DNG_STATUS_OK {
+            return reserve_status;
+        }
+        if let Some(window) = window_mut(ctx, e.handle) {
+            // Pointer events report their own device; emulated mouse events do not.
+            match e.event_type {
+                DNG_EVENT_TOUCH_DOWN..=DNG_EVENT_TOUCH_UP => window.pointer_device_kind = DNG_PTR_TOUCH,
+                DNG_EVENT_MOUSE_DOWN..=DNG_EVENT_MOUSE_UP => window.pointer_device_kind = DNG_PTR_MOUSE,
+                _ => {}
+            }
+            if let Some(mouse) = &mouse {
+                track_primary_touch(window, &e, mouse);
+            }
+        }
+        ctx.host_now_ns = e.timestamp;
+        // Both pushes fit in the room reserved above.
+        push_event(ctx, e);
+        if let Some(mouse) = mouse {
+            push_event(ctx, mouse);
+        }
+        DNG_STATUS_OK
+    })
+}
+
+// Test-only query: number of announcements recorded since the last clear.
+#[no_mangle]
+pub extern "C" fn dngNullWindowGetAnnouncementCount_v1(raw_ctx: *mut c_void, out_count: *mut dng_u32) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_count.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &*state;
+        ptr::write(out_count, ctx.announcement_count);
+        DNG_STATUS_OK
+    })
+}
+
+// Test-only query: the text view points into module storage and stays valid
+// until the next clear or shutdown.
+#[no_mangle]
+pub extern "C" fn dngNullWindowGetAnnouncement_v1(raw_ctx: *mut c_void, index: dng_u32, out_announcement: *mut dng_announcement_v1) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() || out_announcement.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        let ctx = &*state;
+        if index >= ctx.announcement_count {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let record = &*ctx.announcements.add(index as usize);
+        ptr::write(
+            out_announcement,
+            dng_announcement_v1 {
+                handle: record.handle,
+                priority: record.priority,
+                text: dng_str_view_v1 { data: record.text, size: record.text_size },
+            },
+        );
+        DNG_STATUS_OK
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn dngNullWindowClearAnnouncements_v1(raw_ctx: *mut c_void) -> dng_status_v1 {
+    catch_unwind_status(|| unsafe {
+        if raw_ctx.is_null() {
+            return DNG_STATUS_INVALID_ARG;
+        }
+        let Some(mut state) = lock_ctx(raw_ctx) else { return DNG_STATUS_FAIL };
+        clear_announcements(&mut state);
+        DNG_STATUS_OK
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::alloc::{alloc, dealloc, Layout};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct HostStats {
+        live_allocs: AtomicUsize,
+        live_bytes: AtomicUsize,
+        total_allocs: AtomicUsize,
+        fail_allocs: AtomicBool,
+        logs: Mutex<Vec<(dng_u32, String)>>,
+    }
+
+    extern "C" fn test_alloc(user: *mut c_void, size: dng_u64, align: dng_u64) -> *mut c_void {
+        let stats = unsafe { &*(user as *const HostStats) };
+        if stats.fail_allocs.load(Ordering::SeqCst) {
+            return ptr::null_mut();
+        }
+        let layout = match Layout::from_size_align(size as usize, align as usize) {
+            Ok(l) if l.size() > 0 => l,
+            _ => return ptr::null_mut(),
+        };
+        let mem = unsafe { alloc(layout) };
+        if !mem.is_null() {
+            stats.live_allocs.fetch_add(1, Ordering::SeqCst);
+            stats.total_allocs.fetch_add(1, Ordering::SeqCst);
+            stats.live_bytes.fetch_add(layout.size(), Ordering::SeqCst);
+        }
+        mem as *mut c_void
+    }
+
+    extern "C" fn test_free(user: *mut c_void, mem: *mut c_void, size: dng_u64, align: dng_u64) {
+        let stats = unsafe { &*(user as *const HostStats) };
+        let layout = Layout::from_size_align(size as usize, align as usize).expect("free layout must match alloc");
+        unsafe { dealloc(mem as *mut u8, layout) };
+        stats.live_allocs.fetch_sub(1, Ordering::SeqCst);
+        stats.live_bytes.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+
+    extern "C" fn test_log(user: *mut c_void, level: dng_u32, msg: dng_str_view_v1) {
+        let stats = unsafe { &*(user as *const HostStats) };
+        let bytes = unsafe { core::slice::from_raw_parts(msg.data as *const u8, msg.size as usize) };
+        stats.logs.lock().unwrap().push((level, String::from_utf8(bytes.to_vec()).expect("log messages are UTF-8")));
+    }
+
+    fn test_host_api(stats: &HostStats) -> dng_host_api_v1 {
+        dng_host_api_v1 {
+            header: dng_abi_header_v1 { struct_size: size_of::<dng_host_api_v1>() as dng_u32, abi_version: DNG_ABI_VERSION_V1 },
+            user: stats as *const HostStats as *mut c_void,
+            log: None,
+            alloc: Some(test_alloc),
+            free: Some(test_free),
+        }
+    }
+
+    type StrGetter = extern "C" fn(*mut c_void, dng_window_handle_v1, *mut c_char, dng_u32, *mut dng_u32) -> dng_status_v1;
+
+    // Out tables as a host hands them in: zeroed, with every header declaring its size.
+    fn declared_module_api_v1() -> dng_module_api_v1 {
+        let mut api: dng_module_api_v1 = unsafe { core::mem::zeroed() };
+        api.header = table_header::<dng_module_api_v1>(DNG_ABI_VERSION_V1);
+        api.window.header = table_header::<dng_window_api_v1>(DNG_ABI_VERSION_V1);
+        api
+    }
+
+    fn declared_module_api_v2() -> dng_module_api_v2 {
+        let mut api: dng_module_api_v2 = unsafe { core::mem::zeroed() };
+        api.header = table_header::<dng_module_api_v2>(DNG_ABI_VERSION_V2);
+        api.window.header = table_header::<dng_window_api_v2>(DNG_ABI_VERSION_V2);
+        api.audio.header = table_header::<dng_audio_api_v1>(DNG_ABI_VERSION_V1);
+        api
+    }
+
+    struct TestHost {
+        stats: Box<HostStats>,
+        host: Box<dng_host_api_v1>,
+        api: dng_module_api_v2,
+    }
+
+    impl TestHost {
+        fn new() -> TestHost {
+            TestHost::with_host(|_| {})
+        }
+
+        fn with_host(customize: impl FnOnce(&mut dng_host_api_v1)) -> TestHost {
+            let stats = Box::<HostStats>::default();
+            let mut host = Box::new(test_host_api(&stats));
+            customize(&mut host);
+            let mut api = declared_module_api_v2();
+            assert_eq!(dngModuleGetApi_v2(&*host, &mut api), DNG_STATUS_OK);
+            TestHost { stats, host, api }
+        }
+
+        fn ctx(&self) -> *mut c_void {
+            self.api.window.ctx
+        }
+
+        fn create(&self, width: dng_u32, height: dng_u32, title: &[u8]) -> (dng_status_v1, dng_window_handle_v1) {
+            self.create_with_flags(width, height, title, 0)
+        }
+
+        fn create_with_flags(&self, width: dng_u32, height: dng_u32, title: &[u8], flags: dng_u32) -> (dng_status_v1, dng_window_handle_v1) {
+            let desc = dng_window_desc_v1 { width, height, title: str_view(title), flags };
+            let mut handle: dng_window_handle_v1 = 0;
+            let status = (self.api.window.create.unwrap())(self.ctx(), &desc, &mut handle);
+            (status, handle)
+        }
+
+        fn get_size(&self, handle: dng_window_handle_v1) -> (dng_status_v1, dng_window_size_v1) {
+            let mut size = dng_window_size_v1 { width: 0, height: 0 };
+            let status = (self.api.window.get_size.unwrap())(self.ctx(), handle, &mut size);
+            (status, size)
+        }
+
+        fn set_title(&self, handle: dng_window_handle_v1, title: &[u8]) -> dng_status_v1 {
+            (self.api.window.set_title.unwrap())(self.ctx(), handle, str_view(title))
+        }
+
+        fn destroy(&self, handle: dng_window_handle_v1) -> dng_status_v1 {
+            (self.api.window.destroy.unwrap())(self.ctx(), handle)
+        }
+
+        fn shutdown(&self) -> dng_status_v1 {
+            (self.api.shutdown.unwrap())(self.ctx(), &*self.host)
+        }
+
+        fn set_frame_callback_thread(&self, handle: dng_window_handle_v1, thread_id: dng_u64) -> dng_status_v1 {
+            (self.api.window.set_frame_callback_thread.unwrap())(self.ctx(), handle, thread_id)
+        }
+
+        fn frame_callback_thread(&self, handle: dng_window_handle_v1) -> (dng_status_v1, dng_u64) {
+            let mut thread_id = 0xDEAD;
+            let status = (self.api.window.get_frame_callback_thread.unwrap())(self.ctx(), handle, &mut thread_id);
+            (status, thread_id)
+        }
+
+        fn get_title(&self, handle: dng_window_handle_v1) -> (dng_status_v1, Vec<u8>) {
+            self.read_str(self.api.window.get_title.unwrap(), handle)
+        }
+
+        fn set_task_description(&self, handle: dng_window_handle_v1, desc: &[u8]) -> dng_status_v1 {
+            (self.api.window.set_window_task_description.unwrap())(self.ctx(), handle, str_view(desc))
+        }
+
+        fn get_task_description(&self, handle: dng_window_handle_v1) -> (dng_status_v1, Vec<u8>) {
+            self.read_str(self.api.window.get_window_task_description.unwrap(), handle)
+        }
+
+        fn read_str(&self, getter: StrGetter, handle: dng_window_handle_v1) -> (dng_status_v1, Vec<u8>) {
+            let mut size: dng_u32 = 0;
+            let status = getter(self.ctx(), handle, ptr::null_mut(), 0, &mut size);
+            if status != DNG_STATUS_OK {
+                return (status, Vec::new());
+            }
+            let mut buffer = vec![0u8; size as usize];
+            let status = getter(self.ctx(), handle, buffer.as_mut_ptr() as *mut c_char, size, &mut size);
+            (status, buffer)
+        }
+
+        fn get_pointer_device_kind(&self, handle: dng_window_handle_v1) -> (dng_status_v1, dng_pointer_device_kind_v1) {
+            let mut kind: dng_pointer_device_kind_v1 = 0xFFFF;
+            let status = (self.api.window.get_pointer_device_kind.unwrap())(self.ctx(), handle, &mut kind);
+            (status, kind)
+        }
+
+        fn media_keys(&self) -> dng_u32 {
+            let mut keys: dng_u32 = 0xDEAD;
+            assert_eq!((self.api.window.get_media_key_state.unwrap())(self.ctx(), &mut keys), DNG_STATUS_OK);
+            keys
+        }
+
+        fn inject(&self, event: dng_window_event_v1) -> dng_status_v1 {
+            dngNullWindowInjectEvent_v1(self.ctx(), &event)
+        }
+
+        fn poll_event(&self) -> (dng_status_v1, dng_window_event_v1) {
+            let mut event = event(DNG_EVENT_NONE, 0);
+            let status = (self.api.window.poll_event.unwrap())(self.ctx(), &mut event);
+            (status, event)
+        }
+
+        fn announce(&self, handle: dng_window_handle_v1, text: &[u8], priority: dng_announce_priority_v1) -> dng_status_v1 {
+            (self.api.window.announce.unwrap())(self.ctx(), handle, str_view(text), priority)
+        }
+
+        fn announcements(&self) -> Vec<(dng_window_handle_v1, dng_announce_priority_v1, Vec<u8>)> {
+            let mut count: dng_u32 = 0;
+            assert_eq!(dngNullWindowGetAnnouncementCount_v1(self.ctx(), &mut count), DNG_STATUS_OK);
+            (0..count)
+                .map(|i| {
+                    let mut a = dng_announcement_v1 { handle: 0, priority: 0, text: str_view(b"") };
+                    assert_eq!(dngNullWindowGetAnnouncement_v1(self.ctx(), i, &mut a), DNG_STATUS_OK);
+                    let text = if a.text.size == 0 {
+                        Vec::new()
+                    } else {
+                        unsafe { core::slice::from_raw_parts(a.text.data as *const u8, a.text.size as usize) }.to_vec()
+                    };
+                    (a.handle, a.priority, text)
+                })
+                .collect()
+        }
+
+        fn get_flags(&self, handle: dng_window_handle_v1) -> (dng_status_v1, dng_u32) {
+            let mut flags: dng_u32 = 0xFFFF_FFFF;
+            let status = (self.api.window.get_flags.unwrap())(self.ctx(), handle, &mut flags);
+            (status, flags)
+        }
+
+        fn set_parent(&self, handle: dng_window_handle_v1, parent: dng_window_handle_v1) -> dng_status_v1 {
+            (self.api.window.set_parent.unwrap())(self.ctx(), handle, parent)
+        }
+
+        fn ancestors(&self, handle: dng_window_handle_v1) -> (dng_status_v1, Vec<dng_window_handle_v1>) {
+            let get_ancestor_chain = self.api.window.get_ancestor_chain.unwrap();
+            let mut depth: dng_u32 = 0;
+            let status = get_ancestor_chain(self.ctx(), handle, ptr::null_mut(), 0, &mut depth);
+            if status != DNG_STATUS_OK {
+                return (status, Vec::new());
+            }
+            let mut chain = vec![0; depth as usize];
+            let status = get_ancestor_chain(self.ctx(), handle, chain.as_mut_ptr(), depth, &mut depth);
+            (status, chain)
+        }
+
+        fn depth(&self, handle: dng_window_handle_v1) -> (dng_status_v1, dng_u32) {
+            let mut depth: dng_u32 = 0xFFFF;
+            let status = (self.api.window.get_window_depth.unwrap())(self.ctx(), handle, &mut depth);
+            (status, depth)
+        }
+
+        fn send_ipc(&self, src: dng_window_handle_v1, dst: dng_window_handle_v1, kind: dng_u32, payload: &[u8]) -> dng_status_v1 {
+            let send = self.api.window.send_ipc_message.unwrap();
+            send(self.ctx(), src, dst, kind, payload.as_ptr() as *const c_void, payload.len() as dng_u32)
+        }
+
+        fn mark_dirty(&self, handle: dng_window_handle_v1, x: i32, y: i32, width: dng_u32, height: dng_u32) -> dng_status_v1 {
+            (self.api.window.mark_dirty.unwrap())(self.ctx(), handle, dng_rect_v1 { x, y, width, height })
+        }
+
+        fn take_dirty(&self, handle: dng_window_handle_v1) -> (dng_status_v1, dng_rect_v1) {
+            let mut rect = dng_rect_v1 { x: -1, y: -1, width: 1, height: 1 };
+            let status = (self.api.window.get_and_clear_dirty.unwrap())(self.ctx(), handle, &mut rect);
+            (status, rect)
+        }
+
+        fn open_audio(&self, sample_rate: dng_u32, channels: dng_u32) -> (dng_status_v1, dng_audio_device_handle_v1) {
+            let mut device: dng_audio_device_handle_v1 = 0;
+            let status = (self.api.audio.open_device.unwrap())(self.api.audio.ctx, sample_rate, channels, &mut device);
+            (status, device)
+        }
+
+        fn submit_audio(&self, device: dng_audio_device_handle_v1, samples: &[f32], channels: usize, sample_rate: dng_u32) -> dng_status_v1 {
+            let frames = (samples.len() / channels) as dng_u32;
+            (self.api.audio.submit_buffer.unwrap())(self.api.audio.ctx, device, samples.as_ptr(), frames, sample_rate)
+        }
+
+        fn submitted_frames(&self, device: dng_audio_device_handle_v1) -> (dng_status_v1, dng_u64) {
+            let mut frames: dng_u64 = 0;
+            let status = (self.api.audio.get_submitted_frames.unwrap())(self.api.audio.ctx, device, &mut frames);
+            (status, frames)
+        }
+
+        fn take_logs(&self) -> Vec<(dng_u32, String)> {
+            core::mem::take(&mut *self.stats.logs.lock().unwrap())
+        }
+
+        fn live_allocs(&self) -> usize {
+            self.stats.live_allocs.load(Ordering::SeqCst)
+        }
+    }
+
+    fn event(event_type: dng_window_event_type_v1, handle: dng_window_handle_v1) -> dng_window_event_v1 {
+        dng_window_event_v1 { event_type, handle, timestamp: 0, payload: dng_window_event_payload_v1 { reserved: [0; 4] } }
+    }
+
+    fn resize_event(handle: dng_window_handle_v1, width: dng_u32, height: dng_u32) -> dng_window_event_v1 {
+        let mut e = event(DNG_EVENT_RESIZE, handle);
+        e.payload.size = dng_window_size_v1 { width, height };
+        e
+    }
+
+    fn pointer_event(event_type: dng_window_event_type_v1, handle: dng_window_handle_v1, x: i32, y: i32, touch_id: dng_u32) -> dng_window_event_v1 {
+        let mut e = event(event_type, handle);
+        e.payload.pointer = dng_window_pointer_v1 { x, y, touch_id };
+        e
+    }
+
+    fn str_view(bytes: &[u8]) -> dng_str_view_v1 {
+        dng_str_view_v1 { data: bytes.as_ptr() as *const c_char, size: bytes.len() as dng_u32 }
+    }
+
+    #[test]
+    fn multiple_windows_are_tracked_independently() {
+        let host = TestHost::new();
+        let (status, main) = host.create(1280, 720, b"Main");
+        assert_eq!(status, DNG_STATUS_OK);
+        let (status, tool) = host.create(320, 240, b"Tools");
+        assert_eq!(status, DNG_STATUS_OK);
+        assert_ne!(main, tool);
+
+        let (status, size) = host.get_size(main);
+        assert_eq!(status, DNG_STATUS_OK);
+        assert_eq!((size.width, size.height), (1280, 720));
+        let (status, size) = host.get_size(tool);
+        assert_eq!(status, DNG_STATUS_OK);
+        assert_eq!((size.width, size.height), (320, 240));
+
+        assert_eq!(host.destroy(main), DNG_STATUS_OK);
+        assert_eq!(host.get_size(tool).0, DNG_STATUS_OK);
+        assert_eq!(host.set_title(tool, b"Still alive"), DNG_STATUS_OK);
+        assert_eq!(host.destroy(tool), DNG_STATUS_OK);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn recreated_slot_rejects_stale_handle() {
+        let host = TestHost::new();
+        let (_, first) = host.create(64, 64, b"");
+        assert_eq!((dng_handle_slot(first), dng_handle_generation(first)), (0, 1));
+        assert_eq!(host.destroy(first), DNG_STATUS_OK);
+        let (_, second) = host.create(128, 32, b"");
+        assert_eq!((dng_handle_slot(second), dng_handle_generation(second)), (0, 2));
+        assert_ne!(second, first);
+        assert_eq!(host.get_size(second).0, DNG_STATUS_OK);
+
+        assert_eq!(host.get_size(first).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.set_title(first, b"stale"), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.destroy(first), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.destroy(0), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.get_size(second + 100).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn shutdown_frees_every_surviving_window() {
+        let host = TestHost::new();
+        for i in 0..20u32 {
+            let (status, _) = host.create(100 + i, 100, b"window title");
+            assert_eq!(status, DNG_STATUS_OK);
+        }
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn frame_callback_thread_hint_is_stored_per_window() {
+        let host = TestHost::new();
+        let (_, main) = host.create(640, 480, b"");
+        let (_, tool) = host.create(320, 240, b"");
+        assert_eq!(host.frame_callback_thread(main), (DNG_STATUS_OK, 0));
+
+        assert_eq!(host.set_frame_callback_thread(main, 42), DNG_STATUS_OK);
+        assert_eq!(host.frame_callback_thread(main), (DNG_STATUS_OK, 42));
+        assert_eq!(host.frame_callback_thread(tool), (DNG_STATUS_OK, 0));
+
+        assert_eq!(host.set_frame_callback_thread(main, 0), DNG_STATUS_OK);
+        assert_eq!(host.frame_callback_thread(main), (DNG_STATUS_OK, 0));
+
+        assert_eq!(host.destroy(main), DNG_STATUS_OK);
+        assert_eq!(host.set_frame_callback_thread(main, 7), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.set_frame_callback_thread(0, 7), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn get_title_round_trips_create_and_set_title() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"Editor");
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, b"Editor".to_vec()));
+
+        assert_eq!(host.set_title(handle, b"Editor - scene.dng"), DNG_STATUS_OK);
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, b"Editor - scene.dng".to_vec()));
+
+        assert_eq!(host.set_title(handle, b""), DNG_STATUS_OK);
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, Vec::new()));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn get_title_reports_required_size_for_small_buffers() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"Inspector");
+        let get_title = host.api.window.get_title.unwrap();
+
+        let mut buffer = [0xAAu8; 4];
+        let mut size: dng_u32 = 0;
+        let status = get_title(host.ctx(), handle, buffer.as_mut_ptr() as *mut c_char, buffer.len() as dng_u32, &mut size);
+        assert_eq!(status, DNG_STATUS_INVALID_ARG);
+        assert_eq!(size, 9);
+        assert_eq!(buffer, [0xAA; 4]);
+
+        let mut exact = [0u8; 9];
+        let status = get_title(host.ctx(), handle, exact.as_mut_ptr() as *mut c_char, 9, &mut size);
+        assert_eq!(status, DNG_STATUS_OK);
+        assert_eq!(&exact, b"Inspector");
+
+        assert_eq!(get_title(host.ctx(), handle, exact.as_mut_ptr() as *mut c_char, 9, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.destroy(handle), DNG_STATUS_OK);
+        assert_eq!(get_title(host.ctx(), handle, ptr::null_mut(), 0, &mut size), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn get_title_of_empty_title_writes_nothing() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        let mut buffer = [0x55u8; 2];
+        let mut size: dng_u32 = 123;
+        let status = (host.api.window.get_title.unwrap())(host.ctx(), handle, buffer.as_mut_ptr() as *mut c_char, 2, &mut size);
+        assert_eq!(status, DNG_STATUS_OK);
+        assert_eq!(size, 0);
+        assert_eq!(buffer, [0x55; 2]);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn pointer_device_kind_follows_last_injected_input() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        let (_, other) = host.create(640, 480, b"");
+        assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, DNG_PTR_MOUSE));
+
+        for kind in [DNG_PTR_TOUCH, DNG_PTR_STYLUS, DNG_PTR_MOUSE] {
+            assert_eq!(dngNullWindowInjectPointerInput_v1(host.ctx(), handle, kind), DNG_STATUS_OK);
+            assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, kind));
+        }
+        assert_eq!(dngNullWindowInjectPointerInput_v1(host.ctx(), other, DNG_PTR_STYLUS), DNG_STATUS_OK);
+        assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, DNG_PTR_MOUSE));
+
+        // Injected pointer events update the kind too, and mirrored mouse events
+        // from touch emulation keep it at touch.
+        assert_eq!(host.inject(pointer_event(DNG_EVENT_TOUCH_DOWN, handle, 1, 2, 0)), DNG_STATUS_OK);
+        assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, DNG_PTR_TOUCH));
+        assert_eq!(host.inject(pointer_event(DNG_EVENT_MOUSE_MOVE, handle, 3, 4, 0)), DNG_STATUS_OK);
+        assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, DNG_PTR_MOUSE));
+        assert_eq!((host.api.window.set_touch_to_mouse_emulation.unwrap())(host.ctx(), handle, DNG_BOOL_TRUE), DNG_STATUS_OK);
+        assert_eq!(host.inject(pointer_event(DNG_EVENT_TOUCH_UP, handle, 1, 2, 0)), DNG_STATUS_OK);
+        assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, DNG_PTR_TOUCH));
+        assert_eq!(host.inject(resize_event(handle, 800, 600)), DNG_STATUS_OK);
+        assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, DNG_PTR_TOUCH));
+        assert_eq!(host.get_pointer_device_kind(other), (DNG_STATUS_OK, DNG_PTR_STYLUS));
+
+        assert_eq!(dngNullWindowInjectPointerInput_v1(host.ctx(), handle, 3), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.destroy(handle), DNG_STATUS_OK);
+        assert_eq!(host.get_pointer_device_kind(handle).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(dngNullWindowInjectPointerInput_v1(host.ctx(), handle, DNG_PTR_TOUCH), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn set_size_is_reflected_in_get_size() {
+        let host = TestHost::new();
+        let window = &host.api.window;
+        let (create, set_size, get_size, destroy) =
+            (window.create.unwrap(), window.set_size.unwrap(), window.get_size.unwrap(), window.destroy.unwrap());
+
+        let desc = dng_window_desc_v1 { width: 800, height: 600, title: str_view(b"Layout"), flags: 0 };
+        let mut handle: dng_window_handle_v1 = 0;
+        assert_eq!(create(window.ctx, &desc, &mut handle), DNG_STATUS_OK);
+
+        let resized = dng_window_size_v1 { width: 1024, height: 768 };
+        assert_eq!(set_size(window.ctx, handle, &resized), DNG_STATUS_OK);
+        let mut size = dng_window_size_v1 { width: 0, height: 0 };
+        assert_eq!(get_size(window.ctx, handle, &mut size), DNG_STATUS_OK);
+        assert_eq!((size.width, size.height), (1024, 768));
+
+        assert_eq!(destroy(window.ctx, handle), DNG_STATUS_OK);
+        assert_eq!(set_size(window.ctx, handle, &resized), DNG_STATUS_INVALID_ARG);
+        assert_eq!(get_size(window.ctx, handle, &mut size), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn set_size_rejects_invalid_arguments() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(800, 600, b"");
+        let set_size = host.api.window.set_size.unwrap();
+
+        assert_eq!(set_size(host.ctx(), handle, ptr::null()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(set_size(host.ctx(), handle, &dng_window_size_v1 { width: 0, height: 10 }), DNG_STATUS_INVALID_ARG);
+        assert_eq!(set_size(host.ctx(), handle, &dng_window_size_v1 { width: 10, height: 0 }), DNG_STATUS_INVALID_ARG);
+        assert_eq!(set_size(host.ctx(), 0, &dng_window_size_v1 { width: 10, height: 10 }), DNG_STATUS_INVALID_ARG);
+        let (_, size) = host.get_size(handle);
+        assert_eq!((size.width, size.height), (800, 600));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn media_key_bits_are_tracked_independently() {
+        let host = TestHost::new();
+        assert_eq!(host.media_keys(), 0);
+
+        let keys = [DNG_MEDIA_KEY_PLAY, DNG_MEDIA_KEY_PAUSE, DNG_MEDIA_KEY_NEXT, DNG_MEDIA_KEY_PREV];
+        for key in keys {
+            assert_eq!(dngNullWindowInjectMediaKey_v1(host.ctx(), key, DNG_BOOL_TRUE), DNG_STATUS_OK);
+            assert_eq!(host.media_keys(), key);
+            assert_eq!(dngNullWindowInjectMediaKey_v1(host.ctx(), key, DNG_BOOL_FALSE), DNG_STATUS_OK);
+            assert_eq!(host.media_keys(), 0);
+        }
+
+        assert_eq!(dngNullWindowInjectMediaKey_v1(host.ctx(), DNG_MEDIA_KEY_PLAY, DNG_BOOL_TRUE), DNG_STATUS_OK);
+        assert_eq!(dngNullWindowInjectMediaKey_v1(host.ctx(), DNG_MEDIA_KEY_NEXT, DNG_BOOL_TRUE), DNG_STATUS_OK);
+        assert_eq!(host.media_keys(), DNG_MEDIA_KEY_PLAY | DNG_MEDIA_KEY_NEXT);
+        assert_eq!(dngNullWindowInjectMediaKey_v1(host.ctx(), DNG_MEDIA_KEY_PLAY, DNG_BOOL_FALSE), DNG_STATUS_OK);
+        assert_eq!(host.media_keys(), DNG_MEDIA_KEY_NEXT);
+
+        assert_eq!(dngNullWindowInjectMediaKey_v1(host.ctx(), 0, DNG_BOOL_TRUE), DNG_STATUS_INVALID_ARG);
+        assert_eq!(dngNullWindowInjectMediaKey_v1(host.ctx(), 1 << 4, DNG_BOOL_TRUE), DNG_STATUS_INVALID_ARG);
+        assert_eq!(
+            dngNullWindowInjectMediaKey_v1(host.ctx(), DNG_MEDIA_KEY_PLAY | DNG_MEDIA_KEY_PAUSE, DNG_BOOL_TRUE),
+            DNG_STATUS_INVALID_ARG
+        );
+        assert_eq!((host.api.window.get_media_key_state.unwrap())(host.ctx(), ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn injected_events_are_delivered_fifo() {
+        let host = TestHost::new();
+        let (_, main) = host.create(640, 480, b"");
+        let (_, tool) = host.create(320, 240, b"");
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+
+        let mut focus = event(DNG_EVENT_FOCUS, tool);
+        focus.payload.focus = dng_window_focus_v1 { focused: DNG_BOOL_TRUE };
+        assert_eq!(host.inject(resize_event(main, 800, 600)), DNG_STATUS_OK);
+        assert_eq!(host.inject(focus), DNG_STATUS_OK);
+        assert_eq!(host.inject(event(DNG_EVENT_CLOSE, main)), DNG_STATUS_OK);
+
+        let (status, e) = host.poll_event();
+        assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, DNG_EVENT_RESIZE, main));
+        let size = unsafe { e.payload.size };
+        assert_eq!((size.width, size.height), (800, 600));
+        let (status, e) = host.poll_event();
+        assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, DNG_EVENT_FOCUS, tool));
+        assert_eq!(unsafe { e.payload.focus.focused }, DNG_BOOL_TRUE);
+        let (status, e) = host.poll_event();
+        assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, DNG_EVENT_CLOSE, main));
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn event_queue_grows_and_wraps_in_order() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        let mut next_expected = 1;
+        for round in 0..4u32 {
+            for i in 0..(INITIAL_EVENT_CAPACITY * 3) {
+                assert_eq!(host.inject(resize_event(handle, round * 1000 + i + 1, 1)), DNG_STATUS_OK);
+            }
+            for _ in 0..(INITIAL_EVENT_CAPACITY * 2) {
+                let (status, e) = host.poll_event();
+                assert_eq!(status, DNG_STATUS_OK);
+                let width = unsafe { e.payload.size.width };
+                assert!(width >= next_expected);
+                next_expected = width + 1;
+            }
+        }
+        let mut drained = 0;
+        while host.poll_event().0 == DNG_STATUS_OK {
+            drained += 1;
+        }
+        assert_eq!(drained, 4 * INITIAL_EVENT_CAPACITY);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn destroying_a_window_flushes_its_pending_events() {
+        let host = TestHost::new();
+        let (_, main) = host.create(640, 480, b"");
+        let (_, tool) = host.create(320, 240, b"");
+        assert_eq!(host.inject(resize_event(main, 1, 1)), DNG_STATUS_OK);
+        assert_eq!(host.inject(resize_event(tool, 2, 2)), DNG_STATUS_OK);
+        assert_eq!(host.inject(event(DNG_EVENT_CLOSE, main)), DNG_STATUS_OK);
+        assert_eq!(host.inject(event(DNG_EVENT_CLOSE, tool)), DNG_STATUS_OK);
+
+        assert_eq!(host.destroy(main), DNG_STATUS_OK);
+        let (status, e) = host.poll_event();
+        assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, DNG_EVENT_RESIZE, tool));
+        let (status, e) = host.poll_event();
+        assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, DNG_EVENT_CLOSE, tool));
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn event_injection_rejects_invalid_events() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        assert_eq!(host.inject(event(DNG_EVENT_NONE, handle)), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.inject(event(99, handle)), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.inject(event(DNG_EVENT_CLOSE, 0)), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.inject(event(DNG_EVENT_CLOSE, handle + 1)), DNG_STATUS_INVALID_ARG);
+        assert_eq!(dngNullWindowInjectEvent_v1(host.ctx(), ptr::null()), DNG_STATUS_INVALID_ARG);
+        assert_eq!((host.api.window.poll_event.unwrap())(host.ctx(), ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn shutdown_frees_pending_events() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        for _ in 0..40 {
+            assert_eq!(host.inject(event(DNG_EVENT_CLOSE, handle)), DNG_STATUS_OK);
+        }
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn announcements_are_recorded_in_order() {
+        let host = TestHost::new();
+        let (_, main) = host.create(640, 480, b"");
+        let (_, dialog) = host.create(320, 240, b"");
+        assert!(host.announcements().is_empty());
+
+        assert_eq!(host.announce(main, b"Level loaded", DNG_ANNOUNCE_POLITE), DNG_STATUS_OK);
+        assert_eq!(host.announce(dialog, b"Unsaved changes", DNG_ANNOUNCE_ASSERTIVE), DNG_STATUS_OK);
+        assert_eq!(host.announce(main, b"", DNG_ANNOUNCE_POLITE), DNG_STATUS_OK);
+        assert_eq!(
+            host.announcements(),
+            vec![
+                (main, DNG_ANNOUNCE_POLITE, b"Level loaded".to_vec()),
+                (dialog, DNG_ANNOUNCE_ASSERTIVE, b"Unsaved changes".to_vec()),
+                (main, DNG_ANNOUNCE_POLITE, Vec::new()),
+            ]
+        );
+
+        assert_eq!(dngNullWindowClearAnnouncements_v1(host.ctx()), DNG_STATUS_OK);
+        assert!(host.announcements().is_empty());
+        let mut a = dng_announcement_v1 { handle: 0, priority: 0, text: str_view(b"") };
+        assert_eq!(dngNullWindowGetAnnouncement_v1(host.ctx(), 0, &mut a), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn announce_rejects_invalid_arguments() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        let announce = host.api.window.announce.unwrap();
+        assert_eq!(host.announce(handle, b"text", 2), DNG_STATUS_INVALID_ARG);
+        assert_eq!(announce(host.ctx(), handle, dng_str_view_v1 { data: ptr::null(), size: 3 }, DNG_ANNOUNCE_POLITE), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.announce(0, b"text", DNG_ANNOUNCE_POLITE), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.destroy(handle), DNG_STATUS_OK);
+        assert_eq!(host.announce(handle, b"text", DNG_ANNOUNCE_POLITE), DNG_STATUS_INVALID_ARG);
+        assert!(host.announcements().is_empty());
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn announce_validates_text_like_titles() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        assert_eq!(host.announce(handle, &[0x66, 0x6F, 0x80], DNG_ANNOUNCE_POLITE), DNG_STATUS_INVALID_UTF8);
+        assert_eq!(host.announce(handle, &"日本".as_bytes()[..5], DNG_ANNOUNCE_ASSERTIVE), DNG_STATUS_INVALID_UTF8);
+        let over_cap = vec![b'a'; MAX_TITLE_SIZE as usize + 1];
+        assert_eq!(host.announce(handle, &over_cap, DNG_ANNOUNCE_POLITE), DNG_STATUS_INVALID_ARG);
+        assert!(host.announcements().is_empty());
+
+        let at_cap = vec![b'a'; MAX_TITLE_SIZE as usize];
+        assert_eq!(host.announce(handle, &at_cap, DNG_ANNOUNCE_POLITE), DNG_STATUS_OK);
+        assert_eq!(host.announcements().len(), 1);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn shutdown_frees_recorded_announcements() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        for _ in 0..30 {
+            assert_eq!(host.announce(handle, b"Autosave complete", DNG_ANNOUNCE_POLITE), DNG_STATUS_OK);
+        }
+        assert_eq!(host.announcements().len(), 30);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn task_description_is_stored_independently_of_title() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"Editor");
+        assert_eq!(host.get_task_description(handle), (DNG_STATUS_OK, Vec::new()));
+
+        assert_eq!(host.set_task_description(handle, b"Compiling shaders"), DNG_STATUS_OK);
+        assert_eq!(host.get_task_description(handle), (DNG_STATUS_OK, b"Compiling shaders".to_vec()));
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, b"Editor".to_vec()));
+
+        assert_eq!(host.set_title(handle, b"Editor*"), DNG_STATUS_OK);
+        assert_eq!(host.get_task_description(handle), (DNG_STATUS_OK, b"Compiling shaders".to_vec()));
+
+        assert_eq!(host.set_task_description(handle, b""), DNG_STATUS_OK);
+        assert_eq!(host.get_task_description(handle), (DNG_STATUS_OK, Vec::new()));
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, b"Editor*".to_vec()));
+
+        // Invalid text and a failed copy both keep the previous description.
+        assert_eq!(host.set_task_description(handle, b"Idle"), DNG_STATUS_OK);
+        assert_eq!(host.set_task_description(handle, b"Bad \xff"), DNG_STATUS_INVALID_UTF8);
+        assert_eq!(host.set_task_description(handle, &vec![b'a'; MAX_TITLE_SIZE as usize + 1]), DNG_STATUS_INVALID_ARG);
+        host.stats.fail_allocs.store(true, Ordering::SeqCst);
+        assert_eq!(host.set_task_description(handle, b"Saving"), DNG_STATUS_OUT_OF_MEMORY);
+        host.stats.fail_allocs.store(false, Ordering::SeqCst);
+        assert_eq!(host.get_task_description(handle), (DNG_STATUS_OK, b"Idle".to_vec()));
+
+        assert_eq!(host.destroy(handle), DNG_STATUS_OK);
+        assert_eq!(host.set_task_description(handle, b"Idle"), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.get_task_description(handle).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn each_window_flag_is_accepted_and_reported() {
+        let host = TestHost::new();
+        for flag in [DNG_WINDOW_FLAG_RESIZABLE, DNG_WINDOW_FLAG_BORDERLESS, DNG_WINDOW_FLAG_HIDDEN, DNG_WINDOW_FLAG_FULLSCREEN] {
+            let (status, handle) = host.create_with_flags(640, 480, b"", flag);
+            assert_eq!(status, DNG_STATUS_OK);
+            assert_eq!(host.get_flags(handle), (DNG_STATUS_OK, flag));
+            let (_, size) = host.get_size(handle);
+            if flag == DNG_WINDOW_FLAG_FULLSCREEN {
+                assert_eq!((size.width, size.height), (1920, 1080));
+            } else {
+                assert_eq!((size.width, size.height), (640, 480));
+            }
+            assert_eq!(host.destroy(handle), DNG_STATUS_OK);
+        }
+
+        let (_, plain) = host.create(640, 480, b"");
+        assert_eq!(host.get_flags(plain), (DNG_STATUS_OK, 0));
+        let combined = DNG_WINDOW_FLAG_RESIZABLE | DNG_WINDOW_FLAG_HIDDEN;
+        let (_, both) = host.create_with_flags(640, 480, b"", combined);
+        assert_eq!(host.get_flags(both), (DNG_STATUS_OK, combined));
+        assert_eq!(host.destroy(both), DNG_STATUS_OK);
+        assert_eq!(host.get_flags(both).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn unknown_window_flags_are_unsupported() {
+        let host = TestHost::new();
+        for flags in [1 << 4, 1 << 31, DNG_WINDOW_FLAG_RESIZABLE | (1 << 7)] {
+            let (status, handle) = host.create_with_flags(640, 480, b"", flags);
+            assert_eq!(status, DNG_STATUS_UNSUPPORTED);
+            assert_eq!(handle, 0);
+        }
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn ancestor_chain_walks_parents_nearest_first() {
+        let host = TestHost::new();
+        let (_, root) = host.create(1920, 1080, b"");
+        let (_, panel) = host.create(800, 600, b"");
+        let (_, dialog) = host.create(400, 300, b"");
+        let (_, tooltip) = host.create(100, 20, b"");
+        assert_eq!(host.set_parent(panel, root), DNG_STATUS_OK);
+        assert_eq!(host.set_parent(dialog, panel), DNG_STATUS_OK);
+        assert_eq!(host.set_parent(tooltip, dialog), DNG_STATUS_OK);
+
+        assert_eq!(host.ancestors(tooltip), (DNG_STATUS_OK, vec![dialog, panel, root]));
+        assert_eq!(host.ancestors(panel), (DNG_STATUS_OK, vec![root]));
+        assert_eq!(host.ancestors(root), (DNG_STATUS_OK, Vec::new()));
+
+        let get_ancestor_chain = host.api.window.get_ancestor_chain.unwrap();
+        let mut small = [0u64; 2];
+        let mut depth: dng_u32 = 0;
+        assert_eq!(get_ancestor_chain(host.ctx(), tooltip, small.as_mut_ptr(), 2, &mut depth), DNG_STATUS_INVALID_ARG);
+        assert_eq!(depth, 3);
+        assert_eq!(small, [0, 0]);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles_and_dead_windows() {
+        let host = TestHost::new();
+        let (_, a) = host.create(64, 64, b"");
+        let (_, b) = host.create(64, 64, b"");
+        let (_, c) = host.create(64, 64, b"");
+        assert_eq!(host.set_parent(b, a), DNG_STATUS_OK);
+        assert_eq!(host.set_parent(c, b), DNG_STATUS_OK);
+
+        assert_eq!(host.set_parent(a, a), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.set_parent(a, c), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.set_parent(a, c + 100), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.ancestors(a), (DNG_STATUS_OK, Vec::new()));
+
+        assert_eq!(host.destroy(b), DNG_STATUS_OK);
+        assert_eq!(host.ancestors(c), (DNG_STATUS_OK, Vec::new()));
+        assert_eq!(host.set_parent(c, b), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.set_parent(c, a), DNG_STATUS_OK);
+        assert_eq!(host.set_parent(c, 0), DNG_STATUS_OK);
+        assert_eq!(host.ancestors(c), (DNG_STATUS_OK, Vec::new()));
+        assert_eq!(host.ancestors(b).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn query_info_reports_module_metadata() {
+        let mut info = core::mem::MaybeUninit::<dng_module_info>::zeroed();
+        assert_eq!(dngModuleQueryInfo(info.as_mut_ptr()), DNG_STATUS_OK);
+        let info = unsafe { info.assume_init() };
+        assert_eq!(info.header.struct_size as usize, size_of::<dng_module_info>());
+        assert_eq!(info.header.abi_version, DNG_ABI_VERSION_V1);
+        let name = unsafe { core::slice::from_raw_parts(info.module_name.data as *const u8, info.module_name.size as usize) };
+        assert_eq!(name, b"RustNullWindow");
+        assert_eq!((info.module_version_major, info.module_version_minor, info.module_version_patch), (1, 0, 0));
+        assert_eq!((info.min_abi_version, info.max_abi_version), (DNG_ABI_VERSION_V1, DNG_ABI_VERSION_V2));
+        assert_eq!(info.api_families, DNG_API_FAMILY_WINDOW | DNG_API_FAMILY_AUDIO);
+        assert_eq!(dngModuleQueryInfo(ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+    }
+
+    #[test]
+    fn oversized_host_struct_is_accepted() {
+        #[repr(C)]
+        struct ExtendedHost {
+            base: dng_host_api_v1,
+            future_fields: [dng_u64; 4],
+        }
+        let stats = HostStats::default();
+        let mut host = ExtendedHost { base: test_host_api(&stats), future_fields: [0xFFFF_FFFF; 4] };
+        host.base.header.struct_size = size_of::<ExtendedHost>() as dng_u32;
+
+        let mut api = declared_module_api_v1();
+        assert_eq!(dngModuleGetApi_v1(&host.base, &mut api), DNG_STATUS_OK);
+        let desc = dng_window_desc_v1 { width: 64, height: 64, title: str_view(b"ext"), flags: 0 };
+        let mut handle: dng_window_handle_v1 = 0;
+        assert_eq!((api.window.create.unwrap())(api.window.ctx, &desc, &mut handle), DNG_STATUS_OK);
+        assert_eq!((api.shutdown.unwrap())(api.window.ctx, &host.base), DNG_STATUS_OK);
+        assert_eq!(stats.live_allocs.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn undersized_host_struct_and_other_abi_versions_are_rejected() {
+        let stats = HostStats::default();
+        let mut api = declared_module_api_v1();
+
+        let mut host = test_host_api(&stats);
+        host.header.struct_size = size_of::<dng_host_api_v1>() as dng_u32 - 8;
+        assert_eq!(dngModuleGetApi_v1(&host, &mut api), DNG_STATUS_UNSUPPORTED);
+        host.header.struct_size = 0;
+        assert_eq!(dngModuleGetApi_v1(&host, &mut api), DNG_STATUS_UNSUPPORTED);
+
+        for abi_version in [0, 2] {
+            let mut host = test_host_api(&stats);
+            host.header.abi_version = abi_version;
+            assert_eq!(dngModuleGetApi_v1(&host, &mut api), DNG_STATUS_UNSUPPORTED);
+        }
+        assert_eq!(stats.live_allocs.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn window_depth_counts_ancestors() {
+        let host = TestHost::new();
+        let (_, root) = host.create(1920, 1080, b"");
+        let (_, other_root) = host.create(640, 480, b"");
+        let (_, panel) = host.create(800, 600, b"");
+        let (_, dialog) = host.create(400, 300, b"");
+        assert_eq!(host.set_parent(panel, root), DNG_STATUS_OK);
+        assert_eq!(host.set_parent(dialog, panel), DNG_STATUS_OK);
+
+        assert_eq!(host.depth(root), (DNG_STATUS_OK, 0));
+        assert_eq!(host.depth(other_root), (DNG_STATUS_OK, 0));
+        assert_eq!(host.depth(panel), (DNG_STATUS_OK, 1));
+        assert_eq!(host.depth(dialog), (DNG_STATUS_OK, 2));
+
+        assert_eq!(host.set_parent(panel, other_root), DNG_STATUS_OK);
+        assert_eq!(host.depth(dialog), (DNG_STATUS_OK, 2));
+        assert_eq!(host.destroy(panel), DNG_STATUS_OK);
+        assert_eq!(host.depth(dialog), (DNG_STATUS_OK, 0));
+        assert_eq!(host.depth(panel).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!((host.api.window.get_window_depth.unwrap())(host.ctx(), root, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn query_struct_sizes_reports_each_version() {
+        let mut sizes = core::mem::MaybeUninit::<dng_abi_struct_sizes_v1>::zeroed();
+        assert_eq!(dngModuleQueryStructSizes(DNG_ABI_VERSION_V1, sizes.as_mut_ptr()), DNG_STATUS_OK);
+        let sizes = unsafe { sizes.assume_init() };
+        assert_eq!(sizes, abi_struct_sizes_v1());
+        assert_eq!(sizes.header.abi_version, DNG_ABI_VERSION_V1);
+        assert_eq!(sizes.host_api as usize, size_of::<dng_host_api_v1>());
+        assert_eq!(sizes.window_api as usize, size_of::<dng_window_api_v1>());
+
+        let mut other = core::mem::MaybeUninit::<dng_abi_struct_sizes_v1>::zeroed();
+        assert_eq!(dngModuleQueryStructSizes(0, other.as_mut_ptr()), DNG_STATUS_UNSUPPORTED);
+        assert_eq!(dngModuleQueryStructSizes(3, other.as_mut_ptr()), DNG_STATUS_UNSUPPORTED);
+        assert_eq!(dngModuleQueryStructSizes(DNG_ABI_VERSION_V2, other.as_mut_ptr()), DNG_STATUS_OK);
+        let v2 = unsafe { other.assume_init() };
+        assert_eq!(v2, abi_struct_sizes_v2());
+        assert_eq!(v2.module_api as usize, size_of::<dng_module_api_v2>());
+        assert_eq!(v2.window_api as usize, size_of::<dng_window_api_v2>());
+        assert_eq!((sizes.window_event, sizes.audio_api), (0, 0));
+        assert_eq!(dngModuleQueryStructSizes(DNG_ABI_VERSION_V1, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+    }
+
+    #[test]
+    fn ipc_messages_are_routed_to_the_destination_queue() {
+        let host = TestHost::new();
+        let (_, editor) = host.create(1280, 720, b"");
+        let (_, preview) = host.create(640, 360, b"");
+        assert_eq!(host.send_ipc(editor, preview, 7, b"reload"), DNG_STATUS_OK);
+        assert_eq!(host.send_ipc(preview, editor, 8, b""), DNG_STATUS_OK);
+
+        let (status, e) = host.poll_event();
+        assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, DNG_EVENT_IPC, preview));
+        let ipc = unsafe { e.payload.ipc };
+        assert_eq!((ipc.source, ipc.kind, ipc.size), (editor, 7, 6));
+        assert_eq!(&ipc.data[..6], b"reload");
+
+        let (status, e) = host.poll_event();
+        assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, DNG_EVENT_IPC, editor));
+        let ipc = unsafe { e.payload.ipc };
+        assert_eq!((ipc.source, ipc.kind, ipc.size), (preview, 8, 0));
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+
+        assert_eq!(host.send_ipc(editor, preview, 9, b"pending"), DNG_STATUS_OK);
+        assert_eq!(host.destroy(preview), DNG_STATUS_OK);
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn ipc_messages_to_missing_windows_are_not_found() {
+        let host = TestHost::new();
+        let (_, a) = host.create(64, 64, b"");
+        let (_, b) = host.create(64, 64, b"");
+        assert_eq!(host.destroy(b), DNG_STATUS_OK);
+        assert_eq!(host.send_ipc(a, b, 1, b"x"), DNG_STATUS_NOT_FOUND);
+        assert_eq!(host.send_ipc(b, a, 1, b"x"), DNG_STATUS_NOT_FOUND);
+        assert_eq!(host.send_ipc(a, b + 100, 1, b"x"), DNG_STATUS_NOT_FOUND);
+        assert_eq!(host.send_ipc(a, 0, 1, b"x"), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.send_ipc(a, a, 1, &[0u8; DNG_IPC_PAYLOAD_MAX as usize + 1]), DNG_STATUS_INVALID_ARG);
+        let send = host.api.window.send_ipc_message.unwrap();
+        assert_eq!(send(host.ctx(), a, a, 1, ptr::null(), 4), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.send_ipc(a, a, 1, &[0xAB; DNG_IPC_PAYLOAD_MAX as usize]), DNG_STATUS_OK);
+        assert_eq!(host.poll_event().1.event_type, DNG_EVENT_IPC);
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    fn run_window_cycle(host: &TestHost) {
+        let (status, main) = host.create(800, 600, b"Main");
+        assert_eq!(status, DNG_STATUS_OK);
+        let (_, tool) = host.create(200, 100, b"Tool");
+        assert_eq!(host.set_title(main, b"Main - renamed"), DNG_STATUS_OK);
+        assert_eq!(host.set_task_description(tool, b"Baking lightmaps"), DNG_STATUS_OK);
+        assert_eq!(host.inject(resize_event(tool, 300, 150)), DNG_STATUS_OK);
+        assert_eq!(host.announce(main, b"Saved", DNG_ANNOUNCE_POLITE), DNG_STATUS_OK);
+        assert_eq!(host.get_title(main), (DNG_STATUS_OK, b"Main - renamed".to_vec()));
+        assert_eq!(host.destroy(main), DNG_STATUS_OK);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn host_allocator_releases_everything_on_shutdown() {
+        let host = TestHost::new();
+        run_window_cycle(&host);
+        assert!(host.stats.total_allocs.load(Ordering::SeqCst) > 0);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn missing_allocator_falls_back_to_global_allocator() {
+        let host = TestHost::with_host(|h| {
+            h.alloc = None;
+            h.free = None;
+        });
+        run_window_cycle(&host);
+        assert_eq!(host.stats.total_allocs.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn mixed_allocator_callbacks_are_rejected() {
+        let stats = Box::<HostStats>::default();
+        let mut api = declared_module_api_v1();
+        let mut host = test_host_api(&stats);
+        host.free = None;
+        assert_eq!(dngModuleGetApi_v1(&host, &mut api), DNG_STATUS_INVALID_ARG);
+        let mut host = test_host_api(&stats);
+        host.alloc = None;
+        assert_eq!(dngModuleGetApi_v1(&host, &mut api), DNG_STATUS_INVALID_ARG);
+        assert_eq!(stats.live_allocs.load(Ordering::SeqCst), 0);
+    }
+
+    fn rect(x: i32, y: i32, width: dng_u32, height: dng_u32) -> dng_rect_v1 {
+        dng_rect_v1 { x, y, width, height }
+    }
+
+    #[test]
+    fn dirty_region_is_union_of_marks_and_clears_on_read() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"Canvas");
+        // New surfaces start fully dirty.
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_OK, rect(0, 0, 640, 480)));
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_EMPTY, EMPTY_RECT));
+
+        assert_eq!(host.mark_dirty(handle, 10, 10, 20, 20), DNG_STATUS_OK);
+        assert_eq!(host.mark_dirty(handle, 20, 25, 30, 5), DNG_STATUS_OK);
+        assert_eq!(host.mark_dirty(handle, 100, 200, 0, 50), DNG_STATUS_OK);
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_OK, rect(10, 10, 40, 20)));
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_EMPTY, EMPTY_RECT));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn dirty_marks_are_clamped_to_surface() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(100, 50, b"");
+        host.take_dirty(handle);
+
+        assert_eq!(host.mark_dirty(handle, -10, -20, 30, 40), DNG_STATUS_OK);
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_OK, rect(0, 0, 20, 20)));
+        assert_eq!(host.mark_dirty(handle, 90, 40, u32::MAX, u32::MAX), DNG_STATUS_OK);
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_OK, rect(90, 40, 10, 10)));
+        assert_eq!(host.mark_dirty(handle, 100, 0, 10, 10), DNG_STATUS_OK);
+        assert_eq!(host.mark_dirty(handle, i32::MIN, i32::MIN, 5, 5), DNG_STATUS_OK);
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_EMPTY, EMPTY_RECT));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn dirty_union_covers_surfaces_wider_than_i32() {
+        let host = TestHost::new();
+        let (status, handle) = host.create(3_000_000_000, 10, b"");
+        assert_eq!(status, DNG_STATUS_OK);
+        assert_eq!(host.mark_dirty(handle, 0, 0, 1, 1), DNG_STATUS_OK);
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_OK, rect(0, 0, 3_000_000_000, 10)));
+
+        assert_eq!(host.mark_dirty(handle, 0, 0, 1, 1), DNG_STATUS_OK);
+        assert_eq!(host.mark_dirty(handle, i32::MAX - 1, 5, u32::MAX, 1), DNG_STATUS_OK);
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_OK, rect(0, 0, 3_000_000_000, 6)));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn resize_marks_whole_surface_dirty() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(100, 100, b"");
+        host.take_dirty(handle);
+        assert_eq!(host.mark_dirty(handle, 5, 5, 5, 5), DNG_STATUS_OK);
+        let resized = dng_window_size_v1 { width: 300, height: 200 };
+        assert_eq!((host.api.window.set_size.unwrap())(host.ctx(), handle, &resized), DNG_STATUS_OK);
+        assert_eq!(host.take_dirty(handle), (DNG_STATUS_OK, rect(0, 0, 300, 200)));
+
+        let (_, fullscreen) = host.create_with_flags(100, 100, b"", DNG_WINDOW_FLAG_FULLSCREEN);
+        assert_eq!(host.take_dirty(fullscreen), (DNG_STATUS_OK, rect(0, 0, 1920, 1080)));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn dirty_queries_reject_invalid_arguments() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(64, 64, b"");
+        assert_eq!(host.mark_dirty(0, 0, 0, 1, 1), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.mark_dirty(handle + 1, 0, 0, 1, 1), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.take_dirty(handle + 1).0, DNG_STATUS_INVALID_ARG);
+        let get = host.api.window.get_and_clear_dirty.unwrap();
+        assert_eq!(get(host.ctx(), handle, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn titles_accept_multibyte_utf8_and_embedded_nuls() {
+        let host = TestHost::new();
+        let title = "Éditeur – 場面 🎬".as_bytes();
+        let (status, handle) = host.create(64, 64, title);
+        assert_eq!(status, DNG_STATUS_OK);
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, title.to_vec()));
+
+        assert_eq!(host.set_title(handle, b"left\0right\0"), DNG_STATUS_OK);
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, b"left\0right\0".to_vec()));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn titles_reject_invalid_utf8() {
+        let host = TestHost::new();
+        // "é" cut after its lead byte, a stray continuation byte, and an overlong NUL.
+        for bad in [&b"caf\xC3"[..], b"\x80abc", b"\xC0\x80"] {
+            assert_eq!(host.create(64, 64, bad).0, DNG_STATUS_INVALID_UTF8);
+        }
+        let (_, handle) = host.create(64, 64, b"Keep");
+        assert_eq!(host.set_title(handle, &"日本".as_bytes()[..5]), DNG_STATUS_INVALID_UTF8);
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, b"Keep".to_vec()));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn titles_are_capped_at_64_kib() {
+        let host = TestHost::new();
+        let at_cap = vec![b'a'; MAX_TITLE_SIZE as usize];
+        let (status, handle) = host.create(64, 64, &at_cap);
+        assert_eq!(status, DNG_STATUS_OK);
+        let over_cap = vec![b'a'; MAX_TITLE_SIZE as usize + 1];
+        assert_eq!(host.create(64, 64, &over_cap).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.set_title(handle, &over_cap), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.get_title(handle), (DNG_STATUS_OK, at_cap));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn quit_request_is_sticky_until_cancelled() {
+        let host = TestHost::new();
+        let window = &host.api.window;
+        let (request, get, cancel) = (window.request_quit.unwrap(), window.get_quit_requested.unwrap(), window.cancel_quit.unwrap());
+        let requested = || {
+            let mut flag = DNG_BOOL_TRUE;
+            assert_eq!(get(host.ctx(), &mut flag), DNG_STATUS_OK);
+            flag
+        };
+        assert_eq!(requested(), DNG_BOOL_FALSE);
+
+        assert_eq!(request(host.ctx()), DNG_STATUS_OK);
+        assert_eq!(request(host.ctx()), DNG_STATUS_OK);
+        assert_eq!(requested(), DNG_BOOL_TRUE);
+        let (status, e) = host.poll_event();
+        assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, DNG_EVENT_QUIT_REQUESTED, 0));
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+        // Draining the event does not clear the flag.
+        assert_eq!(requested(), DNG_BOOL_TRUE);
+
+        assert_eq!(cancel(host.ctx()), DNG_STATUS_OK);
+        assert_eq!(requested(), DNG_BOOL_FALSE);
+
+        assert_eq!(request(host.ctx()), DNG_STATUS_OK);
+        assert_eq!(requested(), DNG_BOOL_TRUE);
+        assert_eq!(host.poll_event().1.event_type, DNG_EVENT_QUIT_REQUESTED);
+
+        assert_eq!(request(ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(get(host.ctx(), ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(cancel(ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn shutdown_waits_for_calls_blocked_on_the_lock() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        let (get_size, shutdown) = (host.api.window.get_size.unwrap(), host.api.shutdown.unwrap());
+        // Raw pointers are not Send; the module itself is what must be thread-safe.
+        let (ctx, host_api) = (host.ctx() as usize, &*host.host as *const dng_host_api_v1 as usize);
+        let null_ctx = unsafe { &*(host.ctx() as *const NullWindowCtx) };
+
+        let held = lock_state(null_ctx);
+        std::thread::scope(|scope| {
+            let caller = scope.spawn(move || {
+                let mut size = dng_window_size_v1 { width: 0, height: 0 };
+                (get_size(ctx as *mut c_void, handle, &mut size), size.width)
+            });
+            while null_ctx.in_flight.load(Ordering::Acquire) != 1 {
+                std::thread::yield_now();
+            }
+            let closer = scope.spawn(move || shutdown(ctx as *mut c_void, host_api as *const dng_host_api_v1));
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            assert!(!closer.is_finished(), "shutdown must wait for the blocked call");
+
+            drop(held);
+            // Whichever thread takes the lock first, the call never sees freed state.
+            let result = caller.join().unwrap();
+            assert!(result == (DNG_STATUS_OK, 640) || result == (DNG_STATUS_FAIL, 0), "{result:?}");
+            assert_eq!(closer.join().unwrap(), DNG_STATUS_OK);
+        });
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn shutdown_gives_up_on_calls_that_never_leave_and_can_be_retried() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        let (get_size, shutdown) = (host.api.window.get_size.unwrap(), host.api.shutdown.unwrap());
+        let null_ctx = unsafe { &*(host.ctx() as *const NullWindowCtx) };
+
+        // Stands in for a caller that entered the module but never takes the lock.
+        null_ctx.in_flight.fetch_add(1, Ordering::Acquire);
+        assert_eq!(shutdown(host.ctx(), &*host.host), DNG_STATUS_FAIL);
+        null_ctx.in_flight.fetch_sub(1, Ordering::Release);
+
+        // The context stays closed: calls fail without touching it.
+        let mut size = dng_window_size_v1 { width: 0, height: 0 };
+        assert_eq!(get_size(host.ctx(), handle, &mut size), DNG_STATUS_FAIL);
+        assert_eq!(size.width, 0);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn concurrent_calls_from_several_threads_stay_consistent() {
+        const THREADS: dng_u32 = 4;
+        const ITERATIONS: dng_u32 = 300;
+        let host = TestHost::new();
+        let window = &host.api.window;
+        let (create, set_title, get_size, get_title, destroy) = (
+            window.create.unwrap(),
+            window.set_title.unwrap(),
+            window.get_size.unwrap(),
+            window.get_title.unwrap(),
+            window.destroy.unwrap(),
+        );
+        // Raw pointers are not Send; the module itself is what must be thread-safe.
+        let ctx = host.ctx() as usize;
+
+        std::thread::scope(|scope| {
+            for t in 0..THREADS {
+                scope.spawn(move || {
+                    let ctx = ctx as *mut c_void;
+                    for i in 0..ITERATIONS {
+                        let height = 1 + t * ITERATIONS + i;
+                        let desc = dng_window_desc_v1 { width: height * 2, height, title: str_view(b"worker"), flags: 0 };
+                        let mut handle: dng_window_handle_v1 = 0;
+                        assert_eq!(create(ctx, &desc, &mut handle), DNG_STATUS_OK);
+                        assert_eq!(set_title(ctx, handle, str_view(b"worker - busy")), DNG_STATUS_OK);
+                        let mut size = dng_window_size_v1 { width: 0, height: 0 };
+                        assert_eq!(get_size(ctx, handle, &mut size), DNG_STATUS_OK);
+                        assert_eq!((size.width, size.height), (height * 2, height));
+                        assert_eq!(destroy(ctx, handle), DNG_STATUS_OK);
+                    }
+                });
+            }
+            // Races lookups against the workers' destroys: every answer must be a
+            // clean miss or a consistent size and an intact title.
+            scope.spawn(move || {
+                let ctx = ctx as *mut c_void;
+                let mut buffer = [0u8; 32];
+                for _ in 0..4 {
+                    // At most THREADS windows are live at once, so slots stay below THREADS.
+                    for handle in (1..=THREADS * ITERATIONS).flat_map(|generation| (0..THREADS).map(move |slot| dng_make_handle(slot, generation))) {
+                        let mut size = dng_window_size_v1 { width: 0, height: 0 };
+                        match get_size(ctx, handle, &mut size) {
+                            DNG_STATUS_OK => assert_eq!(size.width, size.height * 2),
+                            status => assert_eq!(status, DNG_STATUS_INVALID_ARG),
+                        }
+                        let mut len: dng_u32 = 0;
+                        match get_title(ctx, handle, buffer.as_mut_ptr() as *mut c_char, buffer.len() as dng_u32, &mut len) {
+                            DNG_STATUS_OK => assert!(buffer[..len as usize] == b"worker"[..] || buffer[..len as usize] == b"worker - busy"[..]),
+                            status => assert_eq!(status, DNG_STATUS_INVALID_ARG),
+                        }
+                    }
+                }
+            });
+        });
+
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn touch_to_mouse_emulation_mirrors_primary_touch() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"Kiosk");
+        let set_emulation = host.api.window.set_touch_to_mouse_emulation.unwrap();
+        assert_eq!(set_emulation(host.ctx(), handle, DNG_BOOL_TRUE), DNG_STATUS_OK);
+
+        let sequence = [
+            pointer_event(DNG_EVENT_TOUCH_DOWN, handle, 10, 20, 7),
+            pointer_event(DNG_EVENT_TOUCH_DOWN, handle, 300, 300, 8),
+            pointer_event(DNG_EVENT_TOUCH_MOVE, handle, 15, 25, 7),
+            pointer_event(DNG_EVENT_TOUCH_MOVE, handle, 305, 305, 8),
+            pointer_event(DNG_EVENT_TOUCH_UP, handle, 16, 26, 7),
+            pointer_event(DNG_EVENT_TOUCH_UP, handle, 306, 306, 8),
+        ];
+        for e in &sequence {
+            assert_eq!(host.inject(*e), DNG_STATUS_OK);
+        }
+        let expected = [
+            (DNG_EVENT_TOUCH_DOWN, 10, 20, 7),
+            (DNG_EVENT_MOUSE_DOWN, 10, 20, 0),
+            (DNG_EVENT_TOUCH_DOWN, 300, 300, 8),
+            (DNG_EVENT_TOUCH_MOVE, 15, 25, 7),
+            (DNG_EVENT_MOUSE_MOVE, 15, 25, 0),
+            (DNG_EVENT_TOUCH_MOVE, 305, 305, 8),
+            (DNG_EVENT_TOUCH_UP, 16, 26, 7),
+            (DNG_EVENT_MOUSE_UP, 16, 26, 0),
+            (DNG_EVENT_TOUCH_UP, 306, 306, 8),
+        ];
+        for (event_type, x, y, touch_id) in expected {
+            let (status, e) = host.poll_event();
+            assert_eq!((status, e.event_type, e.handle), (DNG_STATUS_OK, event_type, handle));
+            let pointer = unsafe { e.payload.pointer };
+            assert_eq!((pointer.x, pointer.y, pointer.touch_id), (x, y, touch_id));
+        }
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn touch_events_pass_through_without_emulation() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        let set_emulation = host.api.window.set_touch_to_mouse_emulation.unwrap();
+        assert_eq!(host.inject(pointer_event(DNG_EVENT_TOUCH_DOWN, handle, 1, 1, 0)), DNG_STATUS_OK);
+        assert_eq!(host.poll_event().1.event_type, DNG_EVENT_TOUCH_DOWN);
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+
+        // Enabling mid-touch does not adopt the finger already down.
+        assert_eq!(set_emulation(host.ctx(), handle, DNG_BOOL_TRUE), DNG_STATUS_OK);
+        assert_eq!(host.inject(pointer_event(DNG_EVENT_TOUCH_UP, handle, 1, 1, 0)), DNG_STATUS_OK);
+        assert_eq!(host.poll_event().1.event_type, DNG_EVENT_TOUCH_UP);
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+
+        assert_eq!(set_emulation(host.ctx(), handle, DNG_BOOL_FALSE), DNG_STATUS_OK);
+        assert_eq!(host.inject(pointer_event(DNG_EVENT_TOUCH_DOWN, handle, 2, 2, 0)), DNG_STATUS_OK);
+        assert_eq!(host.poll_event().1.event_type, DNG_EVENT_TOUCH_DOWN);
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+
+        assert_eq!(set_emulation(host.ctx(), 0, DNG_BOOL_TRUE), DNG_STATUS_INVALID_ARG);
+        assert_eq!(set_emulation(host.ctx(), handle + 1, DNG_BOOL_TRUE), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn emulated_touch_that_does_not_fit_changes_nothing() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"");
+        let set_emulation = host.api.window.set_touch_to_mouse_emulation.unwrap();
+        assert_eq!(set_emulation(host.ctx(), handle, DNG_BOOL_TRUE), DNG_STATUS_OK);
+        for i in 0..MAX_QUEUED_EVENTS - 1 {
+            assert_eq!(host.inject(resize_event(handle, i + 1, 1)), DNG_STATUS_OK);
+        }
+
+        // One slot is left but the touch needs two: neither event is queued and
+        // neither the device kind nor the primary touch changes.
+        let touch = pointer_event(DNG_EVENT_TOUCH_DOWN, handle, 5, 6, 2);
+        assert_eq!(host.inject(touch), DNG_STATUS_OUT_OF_MEMORY);
+        assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, DNG_PTR_MOUSE));
+
+        // With room again the same touch is still the first finger down.
+        assert_eq!(host.poll_event().1.event_type, DNG_EVENT_RESIZE);
+        assert_eq!(host.inject(touch), DNG_STATUS_OK);
+        assert_eq!(host.get_pointer_device_kind(handle), (DNG_STATUS_OK, DNG_PTR_TOUCH));
+        for _ in 1..MAX_QUEUED_EVENTS - 1 {
+            assert_eq!(host.poll_event().1.event_type, DNG_EVENT_RESIZE);
+        }
+        assert_eq!(host.poll_event().1.event_type, DNG_EVENT_TOUCH_DOWN);
+        let (status, mouse) = host.poll_event();
+        assert_eq!((status, mouse.event_type), (DNG_STATUS_OK, DNG_EVENT_MOUSE_DOWN));
+        assert_eq!(host.poll_event().0, DNG_STATUS_EMPTY);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn convert_time_maps_host_nanoseconds_to_engine_ticks() {
+        let host = TestHost::new();
+        let (set_time_base, convert_time) = (host.api.window.set_time_base.unwrap(), host.api.window.convert_time.unwrap());
+        let convert = |host_ns: dng_u64| {
+            let mut ticks: dng_u64 = 0;
+            let status = convert_time(host.ctx(), host_ns, &mut ticks);
+            (status, ticks)
+        };
+        // Default mapping is host nanoseconds from zero.
+        assert_eq!(convert(123_456_789), (DNG_STATUS_OK, 123_456_789));
+
+        assert_eq!(set_time_base(host.ctx(), 5 * NANOS_PER_SECOND, 60), DNG_STATUS_OK);
+        assert_eq!(convert(5 * NANOS_PER_SECOND), (DNG_STATUS_OK, 0));
+        assert_eq!(convert(6 * NANOS_PER_SECOND - 1), (DNG_STATUS_OK, 59));
+        assert_eq!(convert(6 * NANOS_PER_SECOND), (DNG_STATUS_OK, 60));
+        assert_eq!(convert(5 * NANOS_PER_SECOND - 1).0, DNG_STATUS_INVALID_ARG);
+
+        assert_eq!(set_time_base(host.ctx(), 0, 0), DNG_STATUS_INVALID_ARG);
+        assert_eq!(convert_time(host.ctx(), 0, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn convert_time_handles_long_uptimes_and_extreme_rates() {
+        let host = TestHost::new();
+        let (set_time_base, convert_time) = (host.api.window.set_time_base.unwrap(), host.api.window.convert_time.unwrap());
+        let convert = |host_ns: dng_u64| {
+            let mut ticks: dng_u64 = 0;
+            let status = convert_time(host.ctx(), host_ns, &mut ticks);
+            (status, ticks)
+        };
+        // 30 days at 10 GHz: elapsed * rate is ~2.6e25, far past u64.
+        let thirty_days_ns = 30 * 24 * 3600 * NANOS_PER_SECOND;
+        assert_eq!(set_time_base(host.ctx(), 0, 10_000_000_000), DNG_STATUS_OK);
+        assert_eq!(convert(thirty_days_ns), (DNG_STATUS_OK, 30 * 24 * 3600 * 10_000_000_000));
+
+        // One tick per second over the whole u64 host range.
+        assert_eq!(set_time_base(host.ctx(), 0, 1), DNG_STATUS_OK);
+        assert_eq!(convert(u64::MAX), (DNG_STATUS_OK, u64::MAX / NANOS_PER_SECOND));
+
+        // The fastest rate overflows after one second but not before.
+        assert_eq!(set_time_base(host.ctx(), 1, u64::MAX), DNG_STATUS_OK);
+        assert_eq!(convert(1 + NANOS_PER_SECOND), (DNG_STATUS_OK, u64::MAX));
+        assert_eq!(convert(2 + NANOS_PER_SECOND).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(convert(1 + NANOS_PER_SECOND / 2), (DNG_STATUS_OK, u64::MAX / 2));
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn events_are_stamped_in_engine_ticks_when_queued() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(640, 480, b"Main");
+        let set_time_base = host.api.window.set_time_base.unwrap();
+        let mut resize = resize_event(handle, 320, 200);
+        resize.timestamp = 5 * NANOS_PER_SECOND + NANOS_PER_SECOND / 2;
+        assert_eq!(host.inject(resize), DNG_STATUS_OK);
+
+        // Already queued events keep the stamp from the old time base.
+        assert_eq!(set_time_base(host.ctx(), 5 * NANOS_PER_SECOND, 60), DNG_STATUS_OK);
+        let mut focus = event(DNG_EVENT_FOCUS, handle);
+        focus.timestamp = 6 * NANOS_PER_SECOND;
+        assert_eq!(host.inject(focus), DNG_STATUS_OK);
+        // Internally queued events use the last injected host time.
+        assert_eq!((host.api.window.request_quit.unwrap())(host.ctx()), DNG_STATUS_OK);
+
+        let (status, polled) = host.poll_event();
+        assert_eq!((status, polled.event_type, polled.timestamp), (DNG_STATUS_OK, DNG_EVENT_RESIZE, 5 * NANOS_PER_SECOND + NANOS_PER_SECOND / 2));
+        let (status, polled) = host.poll_event();
+        assert_eq!((status, polled.event_type, polled.timestamp), (DNG_STATUS_OK, DNG_EVENT_FOCUS, 60));
+        let (status, polled) = host.poll_event();
+        assert_eq!((status, polled.event_type, polled.timestamp), (DNG_STATUS_OK, DNG_EVENT_QUIT_REQUESTED, 60));
+
+        // Host times outside the engine range clamp instead of failing.
+        focus.timestamp = 0;
+        assert_eq!(host.inject(focus), DNG_STATUS_OK);
+        assert_eq!(host.poll_event().1.timestamp, 0);
+        assert_eq!(set_time_base(host.ctx(), 0, u64::MAX), DNG_STATUS_OK);
+        focus.timestamp = 2 * NANOS_PER_SECOND;
+        assert_eq!(host.inject(focus), DNG_STATUS_OK);
+        assert_eq!(host.poll_event().1.timestamp, u64::MAX);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    fn logged(entries: &[(dng_u32, &str)]) -> Vec<(dng_u32, String)> {
+        entries.iter().map(|(level, msg)| (*level, msg.to_string())).collect()
+    }
+
+    #[test]
+    fn lifecycle_is_reported_through_host_log() {
+        let host = TestHost::with_host(|h| h.log = Some(test_log));
+        let (_, handle) = host.create(640, 480, b"Main");
+        assert_eq!(host.set_title(handle, b"Renamed"), DNG_STATUS_OK);
+        assert_eq!(host.destroy(handle), DNG_STATUS_OK);
+        let (_, _) = host.create(32, 16, b"");
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(
+            host.take_logs(),
+            logged(&[
+                (DNG_LOG_LEVEL_INFO, "window 0:1 created (640x480)"),
+                (DNG_LOG_LEVEL_DEBUG, "window 0:1 title set (7 bytes)"),
+                (DNG_LOG_LEVEL_DEBUG, "window 0:1 destroyed"),
+                (DNG_LOG_LEVEL_INFO, "window 0:2 created (32x16)"),
+                (DNG_LOG_LEVEL_INFO, "shutdown: releasing 1 live windows"),
+            ])
+        );
+    }
+
+    #[test]
+    fn failures_are_reported_through_host_log() {
+        let host = TestHost::with_host(|h| h.log = Some(test_log));
+        let (_, handle) = host.create(64, 64, b"");
+        host.take_logs();
+
+        host.stats.fail_allocs.store(true, Ordering::SeqCst);
+        assert_eq!(host.create(64, 64, b"Tools").0, DNG_STATUS_OUT_OF_MEMORY);
+        assert_eq!(host.set_title(handle, b"Renamed"), DNG_STATUS_OUT_OF_MEMORY);
+        host.stats.fail_allocs.store(false, Ordering::SeqCst);
+        assert_eq!(host.destroy(handle + 41), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.destroy(0), DNG_STATUS_INVALID_ARG);
+        assert_eq!(
+            host.take_logs(),
+            logged(&[
+                (DNG_LOG_LEVEL_ERROR, "allocation of 5 bytes (align 1) failed"),
+                (DNG_LOG_LEVEL_ERROR, "allocation of 7 bytes (align 1) failed"),
+                (DNG_LOG_LEVEL_WARN, "destroy: invalid window handle 41:1"),
+                (DNG_LOG_LEVEL_WARN, "destroy: invalid window handle 0:0"),
+            ])
+        );
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn long_log_messages_are_truncated_on_char_boundaries() {
+        let mut buffer = LogBuffer { data: [0; LOG_BUFFER_SIZE], len: 0 };
+        let _ = fmt::write(&mut buffer, format_args!("{}{}", "a".repeat(LOG_BUFFER_SIZE - 1), "é tail"));
+        assert_eq!(buffer.len, LOG_BUFFER_SIZE - 1);
+        assert!(core::str::from_utf8(&buffer.data[..buffer.len]).is_ok());
+    }
+
+    #[test]
+    fn renderer_info_reports_no_renderer() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(64, 64, b"");
+        let get_renderer_info = host.api.window.get_renderer_info.unwrap();
+        let mut info = dng_renderer_info_v1 { kind: 7, api_version: 7, vendor_id: 7, device_id: 7 };
+        assert_eq!(get_renderer_info(host.ctx(), handle, &mut info), DNG_STATUS_OK);
+        assert_eq!(info, dng_renderer_info_v1 { kind: DNG_RENDERER_NONE, api_version: 0, vendor_id: 0, device_id: 0 });
+
+        assert_eq!(get_renderer_info(host.ctx(), handle, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(get_renderer_info(host.ctx(), 0, &mut info), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.destroy(handle), DNG_STATUS_OK);
+        assert_eq!(get_renderer_info(host.ctx(), handle, &mut info), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn slots_survive_out_of_order_destroys() {
+        let host = TestHost::new();
+        let (_, a) = host.create(10, 10, b"a");
+        let (_, b) = host.create(20, 20, b"b");
+        let (_, c) = host.create(30, 30, b"c");
+        assert_eq!(host.destroy(a), DNG_STATUS_OK);
+        assert_eq!(host.get_size(b).1.width, 20);
+        assert_eq!(host.get_size(c).1.width, 30);
+
+        assert_eq!(host.destroy(c), DNG_STATUS_OK);
+        let (_, d) = host.create(40, 40, b"d");
+        let (_, e) = host.create(50, 50, b"e");
+        // Most recently freed slots are reused first.
+        assert_eq!((dng_handle_slot(d), dng_handle_generation(d)), (2, 2));
+        assert_eq!((dng_handle_slot(e), dng_handle_generation(e)), (0, 2));
+        assert_eq!(host.get_title(b), (DNG_STATUS_OK, b"b".to_vec()));
+        assert_eq!(host.get_title(d), (DNG_STATUS_OK, b"d".to_vec()));
+        assert_eq!(host.get_title(e), (DNG_STATUS_OK, b"e".to_vec()));
+        for stale in [a, c] {
+            assert_eq!(host.get_size(stale).0, DNG_STATUS_INVALID_ARG);
+        }
+        assert_eq!(host.get_size(dng_make_handle(3, 1)).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn aspect_hint_is_stored_and_cleared() {
+        let host = TestHost::new();
+        let (_, handle) = host.create(1280, 720, b"");
+        let (set_hint, get_hint) = (host.api.window.set_window_aspect_hint.unwrap(), host.api.window.get_window_aspect_hint.unwrap());
+        let hint = || {
+            let (mut numer, mut denom) = (7, 7);
+            assert_eq!(get_hint(host.ctx(), handle, &mut numer, &mut denom), DNG_STATUS_OK);
+            (numer, denom)
+        };
+        assert_eq!(hint(), (0, 0));
+
+        assert_eq!(set_hint(host.ctx(), handle, 16, 9), DNG_STATUS_OK);
+        assert_eq!(hint(), (16, 9));
+        // Stored as given, not reduced, and never applied to the size.
+        assert_eq!(set_hint(host.ctx(), handle, 1920, 1080), DNG_STATUS_OK);
+        assert_eq!(hint(), (1920, 1080));
+        assert_eq!((host.get_size(handle).1.width, host.get_size(handle).1.height), (1280, 720));
+
+        assert_eq!(set_hint(host.ctx(), handle, 4, 0), DNG_STATUS_INVALID_ARG);
+        assert_eq!(set_hint(host.ctx(), handle, 0, 3), DNG_STATUS_INVALID_ARG);
+        assert_eq!(hint(), (1920, 1080));
+        assert_eq!(set_hint(host.ctx(), handle, 0, 0), DNG_STATUS_OK);
+        assert_eq!(hint(), (0, 0));
+
+        let mut out = 0;
+        assert_eq!(get_hint(host.ctx(), handle, &mut out, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(set_hint(host.ctx(), 0, 1, 1), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.destroy(handle), DNG_STATUS_OK);
+        assert_eq!(set_hint(host.ctx(), handle, 1, 1), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    fn audio_devices_track_submitted_frames() {
+        let host = TestHost::new();
+        assert_eq!(host.api.audio.ctx, host.ctx());
+        let (status, music) = host.open_audio(48_000, 2);
+        assert_eq!(status, DNG_STATUS_OK);
+        let (_, voice) = host.open_audio(16_000, 1);
+
+        let stereo = [0.0f32; 2 * 480];
+        assert_eq!(host.submit_audio(music, &stereo, 2, 48_000), DNG_STATUS_OK);
+        assert_eq!(host.submit_audio(music, &stereo, 2, 48_000), DNG_STATUS_OK);
+        assert_eq!(host.submit_audio(voice, &[0.5f32; 160], 1, 16_000), DNG_STATUS_OK);
+        assert_eq!(host.submitted_frames(music), (DNG_STATUS_OK, 960));
+        assert_eq!(host.submitted_frames(voice), (DNG_STATUS_OK, 160));
+
+        let mut latency: dng_u64 = 0;
+        assert_eq!((host.api.audio.get_latency.unwrap())(host.api.audio.ctx, music, &mut latency), DNG_STATUS_OK);
+        assert_eq!(latency, NULL_AUDIO_LATENCY_US);
+
+        let close = host.api.audio.close_device.unwrap();
+        assert_eq!(close(host.api.audio.ctx, music), DNG_STATUS_OK);
+        assert_eq!(close(host.api.audio.ctx, music), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.submitted_frames(music).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.submitted_frames(voice), (DNG_STATUS_OK, 160));
+        let (_, reopened) = host.open_audio(48_000, 2);
+        assert_ne!(reopened, music);
+        assert_eq!(host.submitted_frames(reopened), (DNG_STATUS_OK, 0));
+
+        // Devices left open are released by shutdown.
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn audio_calls_reject_invalid_arguments() {
+        let host = TestHost::new();
+        assert_eq!(host.open_audio(0, 2).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.open_audio(48_000, 0).0, DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.open_audio(48_000, MAX_AUDIO_CHANNELS + 1).0, DNG_STATUS_UNSUPPORTED);
+        let (_, device) = host.open_audio(44_100, 2);
+
+        let submit = host.api.audio.submit_buffer.unwrap();
+        assert_eq!(submit(host.api.audio.ctx, device, ptr::null(), 16, 44_100), DNG_STATUS_INVALID_ARG);
+        assert_eq!(submit(host.api.audio.ctx, device, ptr::null(), 0, 44_100), DNG_STATUS_OK);
+        assert_eq!(host.submit_audio(device, &[0.0; 64], 2, 48_000), DNG_STATUS_UNSUPPORTED);
+        assert_eq!(host.submit_audio(device + 1, &[0.0; 64], 2, 44_100), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.submit_audio(0, &[0.0; 64], 2, 44_100), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.submitted_frames(device), (DNG_STATUS_OK, 0));
+
+        let mut value: dng_u64 = 0;
+        assert_eq!((host.api.audio.get_latency.unwrap())(host.api.audio.ctx, device, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!((host.api.audio.get_latency.unwrap())(host.api.audio.ctx, 0, &mut value), DNG_STATUS_INVALID_ARG);
+        assert_eq!((host.api.audio.get_submitted_frames.unwrap())(host.api.audio.ctx, device, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!((host.api.audio.close_device.unwrap())(host.api.audio.ctx, 0), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+        assert_eq!(host.live_allocs(), 0);
+    }
+
+    #[test]
+    fn event_accessors_check_the_event_type() {
+        let mut size = dng_window_size_v1 { width: 0, height: 0 };
+        assert_eq!(dngEventGetResize(&resize_event(3, 800, 600), &mut size), DNG_STATUS_OK);
+        assert_eq!((size.width, size.height), (800, 600));
+
+        let mut focus_event = event(DNG_EVENT_FOCUS, 3);
+        focus_event.payload.focus = dng_window_focus_v1 { focused: DNG_BOOL_TRUE };
+        let mut focus = dng_window_focus_v1 { focused: DNG_BOOL_FALSE };
+        assert_eq!(dngEventGetFocus(&focus_event, &mut focus), DNG_STATUS_OK);
+        assert_eq!(focus.focused, DNG_BOOL_TRUE);
+
+        let mut ipc_event = event(DNG_EVENT_IPC, 3);
+        ipc_event.payload.ipc = dng_window_ipc_v1 { source: 4, kind: 9, size: 2, data: [7; DNG_IPC_PAYLOAD_MAX as usize] };
+        let mut ipc = dng_window_ipc_v1 { source: 0, kind: 0, size: 0, data: [0; DNG_IPC_PAYLOAD_MAX as usize] };
+        assert_eq!(dngEventGetIpc(&ipc_event, &mut ipc), DNG_STATUS_OK);
+        assert_eq!((ipc.source, ipc.kind, ipc.size, ipc.data[0]), (4, 9, 2, 7));
+
+        let mut pointer = dng_window_pointer_v1 { x: 0, y: 0, touch_id: 0 };
+        for event_type in DNG_EVENT_TOUCH_DOWN..=DNG_EVENT_MOUSE_UP {
+            assert_eq!(dngEventGetPointer(&pointer_event(event_type, 3, -5, 6, 2), &mut pointer), DNG_STATUS_OK);
+            assert_eq!((pointer.x, pointer.y, pointer.touch_id), (-5, 6, 2));
+        }
+
+        // Every accessor rejects every other type, including payload-less and unknown ones.
+        let other_types = [DNG_EVENT_NONE, DNG_EVENT_CLOSE, DNG_EVENT_QUIT_REQUESTED, 99];
+        for event_type in other_types.into_iter().chain([DNG_EVENT_FOCUS, DNG_EVENT_IPC, DNG_EVENT_MOUSE_UP]) {
+            assert_eq!(dngEventGetResize(&event(event_type, 3), &mut size), DNG_STATUS_INVALID_ARG);
+        }
+        for event_type in other_types.into_iter().chain([DNG_EVENT_RESIZE, DNG_EVENT_IPC, DNG_EVENT_TOUCH_DOWN]) {
+            assert_eq!(dngEventGetFocus(&event(event_type, 3), &mut focus), DNG_STATUS_INVALID_ARG);
+        }
+        for event_type in other_types.into_iter().chain([DNG_EVENT_RESIZE, DNG_EVENT_FOCUS, DNG_EVENT_MOUSE_MOVE]) {
+            assert_eq!(dngEventGetIpc(&event(event_type, 3), &mut ipc), DNG_STATUS_INVALID_ARG);
+        }
+        for event_type in other_types.into_iter().chain([DNG_EVENT_RESIZE, DNG_EVENT_FOCUS, DNG_EVENT_IPC]) {
+            assert_eq!(dngEventGetPointer(&event(event_type, 3), &mut pointer), DNG_STATUS_INVALID_ARG);
+        }
+        assert_eq!(dngEventGetResize(ptr::null(), &mut size), DNG_STATUS_INVALID_ARG);
+        assert_eq!(dngEventGetResize(&resize_event(3, 1, 1), ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+    }
+
+    #[test]
+    fn only_one_window_is_main() {
+        let host = TestHost::new();
+        let (_, first) = host.create(640, 480, b"");
+        let (_, second) = host.create(320, 240, b"");
+        let (get_main, set_main) = (host.api.window.get_is_main_window.unwrap(), host.api.window.set_is_main_window.unwrap());
+        let is_main = |handle| {
+            let mut out = 7;
+            assert_eq!(get_main(host.ctx(), handle, &mut out), DNG_STATUS_OK);
+            out
+        };
+        assert_eq!((is_main(first), is_main(second)), (DNG_BOOL_FALSE, DNG_BOOL_FALSE));
+
+        assert_eq!(set_main(host.ctx(), first, DNG_BOOL_TRUE), DNG_STATUS_OK);
+        assert_eq!((is_main(first), is_main(second)), (DNG_BOOL_TRUE, DNG_BOOL_FALSE));
+        // Promoting another window demotes the previous one.
+        assert_eq!(set_main(host.ctx(), second, DNG_BOOL_TRUE), DNG_STATUS_OK);
+        assert_eq!((is_main(first), is_main(second)), (DNG_BOOL_FALSE, DNG_BOOL_TRUE));
+        // Clearing a non-main window leaves the main window alone.
+        assert_eq!(set_main(host.ctx(), first, DNG_BOOL_FALSE), DNG_STATUS_OK);
+        assert_eq!(is_main(second), DNG_BOOL_TRUE);
+        assert_eq!(set_main(host.ctx(), second, DNG_BOOL_FALSE), DNG_STATUS_OK);
+        assert_eq!((is_main(first), is_main(second)), (DNG_BOOL_FALSE, DNG_BOOL_FALSE));
+
+        // Destroying the main window leaves none, and a recycled slot does not inherit the flag.
+        assert_eq!(set_main(host.ctx(), first, DNG_BOOL_TRUE), DNG_STATUS_OK);
+        assert_eq!(host.destroy(first), DNG_STATUS_OK);
+        let (_, recycled) = host.create(640, 480, b"");
+        assert_eq!(dng_handle_slot(recycled), dng_handle_slot(first));
+        assert_eq!((is_main(recycled), is_main(second)), (DNG_BOOL_FALSE, DNG_BOOL_FALSE));
+
+        let mut out = 0;
+        assert_eq!(get_main(host.ctx(), first, &mut out), DNG_STATUS_INVALID_ARG);
+        assert_eq!(set_main(host.ctx(), first, DNG_BOOL_TRUE), DNG_STATUS_INVALID_ARG);
+        assert_eq!(get_main(host.ctx(), second, ptr::null_mut()), DNG_STATUS_INVALID_ARG);
+        assert_eq!(set_main(host.ctx(), 0, DNG_BOOL_TRUE), DNG_STATUS_INVALID_ARG);
+        assert_eq!(host.shutdown(), DNG_STATUS_OK);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn v1_tables_keep_the_frozen_c_layout() {
+        // DngModuleApi.h / DngWindowApi.h v1 layout on 64-bit targets.
+        assert_eq!(size_of::<dng_window_api_v1>(), 56);
+        assert_eq!(size_of::<dng_module_api_v1>(), 104);
+        assert_eq!(core::mem::offset_of!(dng_module_api_v1, window), 40);
+        assert_eq!(core::mem::offset_of!(dng_module_api_v1, shutdown), 96);
+        assert_eq!(abi_struct_sizes_v1().window_api, 56);
+        assert_eq!(abi_struct_sizes_v1().module_api, 104);
+    }
+
+    #[test]
+    fn v2_window_table_extends_the_v1_prefix() {
+        use core::mem::offset_of;
+        assert_eq!(offset_of!(dng_window_api_v2, ctx), offset_of!(dng_window_api_v1, ctx));
+        assert_eq!(offset_of!(dng_window_api_v2, create), offset_of!(dng_window_api_v1, create));
+        assert_eq!(offset_of!(dng_window_api_v2, destroy), offset_of!(dng_window_api_v1, destroy));
+        assert_eq!(offset_of!(dng_window_api_v2, poll), offset_of!(dng_window_api_v1, poll));
+        assert_eq!(offset_of!(dng_window_api_v2, get_size), offset_of!(dng_window_api_v1, get_size));
+        assert_eq!(offset_of!(dng_window_api_v2, set_title), offset_of!(dng_window_api_v1, set_title));
+        assert_eq!(offset_of!(dng_window_api_v2, set_frame_callback_thread), size_of::<dng_window_api_v1>());
+        assert_eq!(offset_of!(dng_module_api_v2, window), offset_of!(dng_module_api_v1, window));
+    }
+
+    #[test]
+    fn get_api_v1_fills_only_the_declared_v1_table() {
+        #[repr(C)]
+        struct GuardedApi {
+            api: dng_module_api_v1,
+            guard: [u8; 64],
+        }
+        let stats = HostStats::default();
+        let host = test_host_api(&stats);
+        let mut out = GuardedApi { api: declared_module_api_v1(), guard: [0xAA; 64] };
+        assert_eq!(dngModuleGetApi_v1(&host, &mut out.api), DNG_STATUS_OK);
+        assert_eq!(out.guard, [0xAA; 64]);
+        assert_eq!(out.api.header, table_header::<dng_module_api_v1>(DNG_ABI_VERSION_V1));
+        assert_eq!(out.api.window.header, table_header::<dng_window_api_v1>(DNG_ABI_VERSION_V1));
+
+        let api = &out.api;
+        let desc = dng_window_desc_v1 { width: 320, height: 200, title: str_view(b"v1"), flags: 0 };
+        let mut handle: dng_window_handle_v1 = 0;
+        assert_eq!((api.window.create.unwrap())(api.window.ctx, &desc, &mut handle), DNG_STATUS_OK);
+        let mut size = dng_window_size_v1 { width: 0, height: 0 };
+        assert_eq!((api.window.get_size.unwrap())(api.window.ctx, handle, &mut size), DNG_STATUS_OK);
+        assert_eq!((size.width, size.height), (320, 200));
+        assert_eq!((api.shutdown.unwrap())(api.window.ctx, &host), DNG_STATUS_OK);
+        assert_eq!(stats.live_allocs.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn undersized_out_tables_are_rejected() {
+        let stats = HostStats::default();
+        let host = test_host_api(&stats);
+
+        let mut api = declared_module_api_v1();
+        api.header.struct_size -= 8;
+        assert_eq!(dngModuleGetApi_v1(&host, &mut api), DNG_STATUS_UNSUPPORTED);
+        let mut api = declared_module_api_v1();
+        api.window.header.struct_size = 0;
+        assert_eq!(dngModuleGetApi_v1(&host, &mut api), DNG_STATUS_UNSUPPORTED);
+
+        // A v1-sized table is too small for the v2 entry point.
+        let mut api = declared_module_api_v2();
+        api.header.struct_size = size_of::<dng_module_api_v1>() as dng_u32;
+        assert_eq!(dngModuleGetApi_v2(&host, &mut api), DNG_STATUS_UNSUPPORTED);
+        let mut api = declared_module_api_v2();
+        api.window.header.struct_size = size_of::<dng_window_api_v1>() as dng_u32;
+        assert_eq!(dngModuleGetApi_v2(&host, &mut api), DNG_STATUS_UNSUPPORTED);
+        let mut api = declared_module_api_v2();
+        api.audio.header.struct_size = 0;
+        assert_eq!(dngModuleGetApi_v2(&host, &mut api), DNG_STATUS_UNSUPPORTED);
+        assert_eq!(stats.total_allocs.load(Ordering::SeqCst), 0);
+
+        let mut api = declared_module_api_v2();
+        assert_eq!(dngModuleGetApi_v2(&host, &mut api), DNG_STATUS_OK);
+        assert_eq!(api.header, table_header::<dng_module_api_v2>(DNG_ABI_VERSION_V2));
+        assert_eq!(api.window.header, table_header::<dng_window_api_v2>(DNG_ABI_VERSION_V2));
+        assert_eq!((api.shutdown.unwrap())(api.window.ctx, &host), DNG_STATUS_OK);
+    }
+}
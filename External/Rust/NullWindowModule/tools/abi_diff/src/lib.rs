@@ -0,0 +1,436 @@
+//! ABI compatibility report between two dng module builds.
+//!
+//! A snapshot captures what a module reports through `dngModuleQueryInfo` and
+//! `dngModuleQueryStructSizes`. Snapshots come from a module library or from a
+//! text fixture, and `compare` reports every difference, flagging the ones
+//! that would break a host built against the older module.
+//!
+//! Every struct in a size report is allocated or embedded by the host, so
+//! within one ABI version a size change either way breaks older hosts; only a
+//! struct that did not exist before (size 0) may appear.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem::MaybeUninit;
+
+use rust_null_window_module::{
+    dng_abi_header_v1, dng_abi_struct_sizes_v1, dng_module_info, dng_status_v1, dng_u32, dng_u64, DNG_API_FAMILY_AUDIO,
+    DNG_API_FAMILY_WINDOW, DNG_STATUS_OK,
+};
+
+pub type QueryInfoFn = unsafe extern "C" fn(*mut dng_module_info) -> dng_status_v1;
+pub type QueryStructSizesFn = unsafe extern "C" fn(dng_u32, *mut dng_abi_struct_sizes_v1) -> dng_status_v1;
+
+// Widest ABI range a snapshot may claim; anything larger comes from a broken
+// module and would otherwise mean billions of size queries.
+const MAX_ABI_SPAN: u32 = 64;
+
+fn check_abi_range(min_abi: u32, max_abi: u32) -> Result<(), String> {
+    if max_abi < min_abi || max_abi - min_abi >= MAX_ABI_SPAN {
+        return Err(format!("invalid ABI version range {}..{} (at most {} versions)", min_abi, max_abi, MAX_ABI_SPAN));
+    }
+    Ok(())
+}
+
+const FAMILY_NAMES: [(dng_u64, &str); 2] = [(DNG_API_FAMILY_WINDOW, "window"), (DNG_API_FAMILY_AUDIO, "audio")];
+
+fn size_fields(sizes: &dng_abi_struct_sizes_v1) -> [(&'static str, dng_u32); 7] {
+    [
+        ("host_api", sizes.host_api),
+        ("module_api", sizes.module_api),
+        ("window_api", sizes.window_api),
+        ("window_desc", sizes.window_desc),
+        ("window_event", sizes.window_event),
+        ("module_info", sizes.module_info),
+        ("audio_api", sizes.audio_api),
+    ]
+}
+
+fn set_size_field(sizes: &mut dng_abi_struct_sizes_v1, field: &str, value: dng_u32) -> bool {
+    let slot = match field {
+        "host_api" => &mut sizes.host_api,
+        "module_api" => &mut sizes.module_api,
+        "window_api" => &mut sizes.window_api,
+        "window_desc" => &mut sizes.window_desc,
+        "window_event" => &mut sizes.window_event,
+        "module_info" => &mut sizes.module_info,
+        "audio_api" => &mut sizes.audio_api,
+        _ => return false,
+    };
+    *slot = value;
+    true
+}
+
+fn empty_sizes(abi_version: dng_u32) -> dng_abi_struct_sizes_v1 {
+    dng_abi_struct_sizes_v1 {
+        header: dng_abi_header_v1 { struct_size: std::mem::size_of::<dng_abi_struct_sizes_v1>() as dng_u32, abi_version },
+        host_api: 0,
+        module_api: 0,
+        window_api: 0,
+        window_desc: 0,
+        window_event: 0,
+        module_info: 0,
+        audio_api: 0,
+    }
+}
+
+fn family_list(families: dng_u64) -> String {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = families;
+    for (bit, name) in FAMILY_NAMES {
+        if families & bit != 0 {
+            names.push(name.to_string());
+            rest &= !bit;
+        }
+    }
+    if rest != 0 {
+        names.push(format!("{:#x}", rest));
+    }
+    if names.is_empty() {
+        return "none".to_string();
+    }
+    names.join(",")
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiSnapshot {
+    pub name: String,
+    pub version: (u32, u32, u32),
+    pub min_abi: u32,
+    pub max_abi: u32,
+    pub families: u64,
+    // Keyed by ABI version; versions the module could not describe are absent.
+    pub struct_sizes: BTreeMap<u32, dng_abi_struct_sizes_v1>,
+}
+
+impl AbiSnapshot {
+    /// Builds a snapshot by calling a module's query entry points.
+    ///
+    /// # Safety
+    /// The function pointers must implement the query entry point contracts
+    /// and stay valid for the duration of the call.
+    pub unsafe fn from_entry_points(query_info: QueryInfoFn, query_sizes: Option<QueryStructSizesFn>) -> Result<AbiSnapshot, String> {
+        let mut info = MaybeUninit::<dng_module_info>::zeroed();
+        let status = query_info(info.as_mut_ptr());
+        if status != DNG_STATUS_OK {
+            return Err(format!("dngModuleQueryInfo failed with status {}", status));
+        }
+        let info = info.assume_init();
+        check_abi_range(info.min_abi_version, info.max_abi_version)?;
+        let name = if info.module_name.size == 0 || info.module_name.data.is_null() {
+            String::new()
+        } else {
+            let bytes = std::slice::from_raw_parts(info.module_name.data as *const u8, info.module_name.size as usize);
+            String::from_utf8_lossy(bytes).into_owned()
+        };
+        let mut struct_sizes = BTreeMap::new();
+        if let Some(query_sizes) = query_sizes {
+            for abi_version in info.min_abi_version..=info.max_abi_version {
+                let mut sizes = MaybeUninit::<dng_abi_struct_sizes_v1>::zeroed();
+                if query_sizes(abi_version, sizes.as_mut_ptr()) == DNG_STATUS_OK {
+                    struct_sizes.insert(abi_version, sizes.assume_init());
+                }
+            }
+        }
+        Ok(AbiSnapshot {
+            name,
+            version: (info.module_version_major, info.module_version_minor, info.module_version_patch),
+            min_abi: info.min_abi_version,
+            max_abi: info.max_abi_version,
+            families: info.api_families,
+            struct_sizes,
+        })
+    }
+
+    /// Loads a module library and snapshots it through its exported queries.
+    pub fn load_library(path: &str) -> Result<AbiSnapshot, String> {
+        unsafe {
+            let library = libloading::Library::new(path).map_err(|e| format!("failed to load {}: {}", path, e))?;
+            let query_info = *library
+                .get::<QueryInfoFn>(b"dngModuleQueryInfo\0")
+                .map_err(|e| format!("{} does not export dngModuleQueryInfo: {}", path, e))?;
+            let query_sizes = library.get::<QueryStructSizesFn>(b"dngModuleQueryStructSizes\0").ok().map(|f| *f);
+            // The snapshot copies everything it needs before the library unloads.
+            AbiSnapshot::from_entry_points(query_info, query_sizes)
+        }
+    }
+
+    /// Parses the `key=value` snapshot format produced by `to_text`.
+    pub fn parse(text: &str) -> Result<AbiSnapshot, String> {
+        let mut snapshot = AbiSnapshot {
+            name: String::new(),
+            version: (0, 0, 0),
+            min_abi: 0,
+            max_abi: 0,
+            families: 0,
+            struct_sizes: BTreeMap::new(),
+        };
+        for (number, raw_line) in text.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |what: &str| format!("line {}: {}: {}", number + 1, what, line);
+            let (key, value) = line.split_once('=').ok_or_else(|| err("expected key=value"))?;
+            let number_value = |v: &str| -> Result<u64, String> {
+                let parsed = match v.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => v.parse(),
+                };
+                parsed.map_err(|_| err("invalid number"))
+            };
+            match key {
+                "name" => snapshot.name = value.to_string(),
+                "version" => {
+                    let parts: Vec<&str> = value.split('.').collect();
+                    if parts.len() != 3 {
+                        return Err(err("expected major.minor.patch"));
+                    }
+                    snapshot.version =
+                        (number_value(parts[0])? as u32, number_value(parts[1])? as u32, number_value(parts[2])? as u32);
+                }
+                "min_abi" => snapshot.min_abi = number_value(value)? as u32,
+                "max_abi" => snapshot.max_abi = number_value(value)? as u32,
+                "families" => snapshot.families = number_value(value)?,
+                _ => {
+                    let (version, field) = key
+                        .strip_prefix('v')
+                        .and_then(|rest| rest.split_once('.'))
+                        .ok_or_else(|| err("unknown key"))?;
+                    let version = version.parse::<u32>().map_err(|_| err("invalid ABI version"))?;
+                    let size = number_value(value)? as dng_u32;
+                    let sizes = snapshot.struct_sizes.entry(version).or_insert_with(|| empty_sizes(version));
+                    if !set_size_field(sizes, field, size) {
+                        return Err(err("unknown struct"));
+                    }
+                }
+            }
+        }
+        check_abi_range(snapshot.min_abi, snapshot.max_abi)?;
+        Ok(snapshot)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("name={}\n", self.name));
+        out.push_str(&format!("version={}.{}.{}\n", self.version.0, self.version.1, self.version.2));
+        out.push_str(&format!("min_abi={}\n", self.min_abi));
+        out.push_str(&format!("max_abi={}\n", self.max_abi));
+        out.push_str(&format!("families={:#x}\n", self.families));
+        for (version, sizes) in &self.struct_sizes {
+            for (field, size) in size_fields(sizes) {
+                out.push_str(&format!("v{}.{}={}\n", version, field, size));
+            }
+        }
+        out
+    }
+
+    fn supports(&self, abi_version: u32) -> bool {
+        self.min_abi <= abi_version && abi_version <= self.max_abi
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub lines: Vec<String>,
+    pub incompatibilities: Vec<String>,
+}
+
+impl Report {
+    pub fn is_compatible(&self) -> bool {
+        self.incompatibilities.is_empty()
+    }
+
+    fn note(&mut self, line: String) {
+        self.lines.push(line);
+    }
+
+    fn breaking(&mut self, line: String) {
+        self.lines.push(format!("{} [INCOMPATIBLE]", line));
+        self.incompatibilities.push(line);
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        if self.is_compatible() {
+            writeln!(f, "result: compatible")
+        } else {
+            writeln!(f, "result: incompatible ({} issue(s))", self.incompatibilities.len())
+        }
+    }
+}
+
+/// Compares an older snapshot against a newer one from the older host's
+/// point of view: anything the old module offered must still be there.
+pub fn compare(old: &AbiSnapshot, new: &AbiSnapshot) -> Report {
+    let mut report = Report::default();
+    report.note(format!(
+        "module: {} {}.{}.{} -> {} {}.{}.{}",
+        old.name, old.version.0, old.version.1, old.version.2, new.name, new.version.0, new.version.1, new.version.2
+    ));
+
+    report.note(format!("abi versions: {}..{} -> {}..{}", old.min_abi, old.max_abi, new.min_abi, new.max_abi));
+    for version in old.min_abi..=old.max_abi {
+        if !new.supports(version) {
+            report.breaking(format!("  removed abi version {}", version));
+        }
+    }
+    for version in new.min_abi..=new.max_abi {
+        if !old.supports(version) {
+            report.note(format!("  added abi version {}", version));
+        }
+    }
+
+    report.note(format!("capabilities: {} -> {}", family_list(old.families), family_list(new.families)));
+    let removed = old.families & !new.families;
+    let added = new.families & !old.families;
+    if removed != 0 {
+        report.breaking(format!("  removed capabilities: {}", family_list(removed)));
+    }
+    if added != 0 {
+        report.note(format!("  added capabilities: {}", family_list(added)));
+    }
+
+    for (version, old_sizes) in &old.struct_sizes {
+        if !new.supports(*version) {
+            continue;
+        }
+        let new_sizes = match new.struct_sizes.get(version) {
+            Some(s) => s,
+            None => {
+                report.breaking(format!("struct sizes (v{}): not reported by the new module", version));
+                continue;
+            }
+        };
+        report.note(format!("struct sizes (v{}):", version));
+        for ((field, old_size), (_, new_size)) in size_fields(old_sizes).into_iter().zip(size_fields(new_sizes)) {
+            let line = format!("  {}: {} -> {}", field, old_size, new_size);
+            if new_size < old_size {
+                report.breaking(format!("{} (shrunk)", line));
+            } else if old_size == 0 && new_size > 0 {
+                report.note(format!("{} (added)", line));
+            } else if new_size > old_size {
+                // Later fields move in the host's copy (e.g. module_api.shutdown
+                // after an embedded window_api), so growth needs a new version.
+                report.breaking(format!("{} (grown)", line));
+            } else {
+                report.note(line);
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_null_window_module::{dngModuleQueryInfo, dngModuleQueryStructSizes};
+
+    const PREVIOUS_ABI_V1: &str = include_str!("../fixtures/previous_abi_v1.txt");
+
+    fn current() -> AbiSnapshot {
+        unsafe { AbiSnapshot::from_entry_points(dngModuleQueryInfo, Some(dngModuleQueryStructSizes)) }.unwrap()
+    }
+
+    #[test]
+    fn snapshot_text_round_trips() {
+        let snapshot = current();
+        assert_eq!(AbiSnapshot::parse(&snapshot.to_text()), Ok(snapshot));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn current_build_is_compatible_with_previous_abi() {
+        let previous = AbiSnapshot::parse(PREVIOUS_ABI_V1).unwrap();
+        let report = compare(&previous, &current());
+        assert!(report.is_compatible(), "{}", report);
+        let text = report.to_string();
+        assert!(text.contains("  module_api: 104 -> 104\n"), "{}", text);
+        assert!(text.contains("  window_api: 56 -> 56\n"), "{}", text);
+        assert!(text.contains("module_info: 0 -> "), "{}", text);
+        assert!(text.ends_with("result: compatible\n"), "{}", text);
+    }
+
+    #[test]
+    fn dropping_a_version_or_capability_is_incompatible() {
+        let previous = AbiSnapshot::parse(PREVIOUS_ABI_V1).unwrap();
+        let mut next = previous.clone();
+        next.min_abi = 2;
+        next.max_abi = 2;
+        next.families = DNG_API_FAMILY_AUDIO;
+        let report = compare(&previous, &next);
+        assert!(!report.is_compatible());
+        assert_eq!(report.incompatibilities, vec!["  removed abi version 1".to_string(), "  removed capabilities: window".to_string()]);
+        assert!(report.lines.contains(&"  added abi version 2".to_string()));
+        assert!(report.lines.contains(&"  added capabilities: audio".to_string()));
+    }
+
+    #[test]
+    fn shrinking_a_struct_is_incompatible() {
+        let previous = AbiSnapshot::parse(PREVIOUS_ABI_V1).unwrap();
+        let mut next = previous.clone();
+        next.struct_sizes.get_mut(&1).unwrap().window_api -= 8;
+        let report = compare(&previous, &next);
+        assert_eq!(report.incompatibilities.len(), 1);
+        assert!(report.incompatibilities[0].contains("window_api"));
+
+        next.struct_sizes.clear();
+        let report = compare(&previous, &next);
+        assert_eq!(report.incompatibilities, vec!["struct sizes (v1): not reported by the new module".to_string()]);
+    }
+
+    #[test]
+    fn growing_a_struct_within_a_version_is_incompatible() {
+        // The v1 layout once appended window callbacks in place, which moved
+        // module_api.shutdown for hosts built against the baseline.
+        let previous = AbiSnapshot::parse(PREVIOUS_ABI_V1).unwrap();
+        let mut next = previous.clone();
+        let sizes = next.struct_sizes.get_mut(&1).unwrap();
+        sizes.window_api = 272;
+        sizes.module_api = 320;
+        let report = compare(&previous, &next);
+        assert_eq!(
+            report.incompatibilities,
+            vec!["  module_api: 104 -> 320 (grown)".to_string(), "  window_api: 56 -> 272 (grown)".to_string()]
+        );
+
+        // New versions may carry larger tables without affecting v1 hosts.
+        let mut next = previous.clone();
+        next.max_abi = 2;
+        let mut v2 = empty_sizes(2);
+        v2.window_api = 272;
+        next.struct_sizes.insert(2, v2);
+        assert!(compare(&previous, &next).is_compatible());
+    }
+
+    unsafe extern "C" fn query_info_unbounded(out: *mut dng_module_info) -> dng_status_v1 {
+        assert_eq!(dngModuleQueryInfo(out), DNG_STATUS_OK);
+        (*out).max_abi_version = u32::MAX;
+        DNG_STATUS_OK
+    }
+
+    unsafe extern "C" fn query_sizes_unreachable(_: dng_u32, _: *mut dng_abi_struct_sizes_v1) -> dng_status_v1 {
+        panic!("struct sizes must not be queried for an invalid ABI range");
+    }
+
+    #[test]
+    fn unbounded_abi_ranges_are_rejected() {
+        let err = unsafe { AbiSnapshot::from_entry_points(query_info_unbounded, Some(query_sizes_unreachable)) }.unwrap_err();
+        assert!(err.contains("invalid ABI version range"), "{}", err);
+        assert!(AbiSnapshot::parse("min_abi=1\nmax_abi=4294967295").unwrap_err().contains("invalid ABI version range"));
+        assert!(AbiSnapshot::parse("min_abi=3\nmax_abi=2").is_err());
+        assert!(AbiSnapshot::parse("min_abi=1\nmax_abi=64").is_ok());
+    }
+
+    #[test]
+    fn parse_reports_bad_lines() {
+        assert!(AbiSnapshot::parse("name").unwrap_err().contains("line 1"));
+        assert!(AbiSnapshot::parse("# ok\nversion=1.0").unwrap_err().contains("line 2"));
+        assert!(AbiSnapshot::parse("v1.unknown_struct=4").is_err());
+        assert!(AbiSnapshot::parse("min_abi=x").is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use std::process::ExitCode;
+
+use dng_abi_diff::{compare, AbiSnapshot};
+
+const USAGE: &str = "usage:
+  dng_abi_diff <old> <new>         compare two modules (exit 1 if incompatible)
+  dng_abi_diff --snapshot <module> print a snapshot of a module library
+
+<old>/<new> are module libraries or snapshot files ending in .txt";
+
+fn snapshot(path: &str) -> Result<AbiSnapshot, String> {
+    if path.ends_with(".txt") {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+        return AbiSnapshot::parse(&text).map_err(|e| format!("{}: {}", path, e));
+    }
+    AbiSnapshot::load_library(path)
+}
+
+fn run(args: &[String]) -> Result<bool, String> {
+    match args {
+        [flag, module] if flag == "--snapshot" => {
+            print!("{}", snapshot(module)?.to_text());
+            Ok(true)
+        }
+        [old, new] => {
+            let report = compare(&snapshot(old)?, &snapshot(new)?);
+            print!("{}", report);
+            Ok(report.is_compatible())
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
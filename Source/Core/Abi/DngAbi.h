@@ -42,6 +42,7 @@ typedef int32_t  dng_i32;
 typedef float    dng_f32;
 
 enum { DNG_ABI_VERSION_V1 = 1u };
+enum { DNG_ABI_VERSION_V2 = 2u }; // Adds extended tables; v1 tables stay unchanged.
 
 typedef struct dng_abi_header_v1 {
     dng_u32 struct_size; // Caller sets to sizeof(the containing struct) before use.
@@ -55,6 +56,9 @@ typedef dng_u32 dng_status_v1;
 #define DNG_STATUS_INVALID_ARG   ((dng_status_v1)2u)
 #define DNG_STATUS_OUT_OF_MEMORY ((dng_status_v1)3u)
 #define DNG_STATUS_UNSUPPORTED   ((dng_status_v1)4u)
+#define DNG_STATUS_EMPTY         ((dng_status_v1)5u) // Nothing to return (e.g. empty event queue).
+#define DNG_STATUS_NOT_FOUND     ((dng_status_v1)6u) // Referenced object does not exist.
+#define DNG_STATUS_INVALID_UTF8  ((dng_status_v1)7u) // String view is not valid UTF-8.
 
 typedef dng_u8 dng_bool_v1;
 #define DNG_BOOL_FALSE ((dng_bool_v1)0u)
@@ -0,0 +1,46 @@
+// ============================================================================
+// D-Engine - Core/Abi/DngAudioApi.h
+// ----------------------------------------------------------------------------
+// Purpose : Audio subsystem ABI (v1) using C99 POD types and function tables.
+// Contract: C ABI, POD-only; functions return dng_status_v1; no exceptions or
+//           RTTI; ownership is explicit; host must not touch ctx internals.
+// Notes   : Exported through dng_module_api_v2.audio. ABI v1 is frozen once
+//           published. Thread-safety is defined by the module. ASCII-only.
+// ============================================================================
+#ifndef DNG_ABI_DNG_AUDIO_API_H
+#define DNG_ABI_DNG_AUDIO_API_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#include "DngAbi.h"
+
+typedef dng_u64 dng_audio_device_handle_v1; // 0 is invalid.
+
+typedef struct dng_audio_api_v1 {
+    dng_abi_header_v1 header; // { struct_size, abi_version }
+    void*             ctx;    // Module-owned context; may equal the window ctx.
+
+    // Purpose : Open an output device at a fixed rate and channel count.
+    // Contract: sample_rate and channels non-zero; out_device non-null.
+    dng_status_v1 (DNG_ABI_CALL *open_device)(void* ctx, dng_u32 sample_rate, dng_u32 channels, dng_audio_device_handle_v1* out_device);
+    dng_status_v1 (DNG_ABI_CALL *close_device)(void* ctx, dng_audio_device_handle_v1 device);
+
+    // Purpose : Queue interleaved f32 samples (frame_count * channels values).
+    // Contract: frame_count > 0 requires non-NULL samples; a sample_rate other
+    //           than the device rate returns DNG_STATUS_UNSUPPORTED.
+    dng_status_v1 (DNG_ABI_CALL *submit_buffer)(void* ctx, dng_audio_device_handle_v1 device, const dng_f32* samples, dng_u32 frame_count, dng_u32 sample_rate);
+
+    // Purpose : Report output latency in microseconds.
+    dng_status_v1 (DNG_ABI_CALL *get_latency)(void* ctx, dng_audio_device_handle_v1 device, dng_u64* out_latency_us);
+
+    // Purpose : Report the total number of frames submitted to a device.
+    dng_status_v1 (DNG_ABI_CALL *get_submitted_frames)(void* ctx, dng_audio_device_handle_v1 device, dng_u64* out_frames);
+} dng_audio_api_v1;
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif // DNG_ABI_DNG_AUDIO_API_H
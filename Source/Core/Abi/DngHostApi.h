@@ -16,12 +16,20 @@ extern "C" {
 
 #include "DngAbi.h"
 
+// Levels passed to dng_host_api_v1.log, in increasing severity. Hosts may map
+// them to their own logger levels.
+#define DNG_LOG_LEVEL_TRACE ((dng_u32)0u)
+#define DNG_LOG_LEVEL_DEBUG ((dng_u32)1u)
+#define DNG_LOG_LEVEL_INFO  ((dng_u32)2u)
+#define DNG_LOG_LEVEL_WARN  ((dng_u32)3u)
+#define DNG_LOG_LEVEL_ERROR ((dng_u32)4u)
+
 typedef struct dng_host_api_v1 {
     dng_abi_header_v1 header;  // { struct_size, abi_version }
     void*             user;    // Non-owning user pointer provided by the host.
 
-    // Purpose : Log a message with a host-defined level.
-    // Contract: Never throws; msg is a non-owning view; level is host-defined.
+    // Purpose : Log a message at a DNG_LOG_LEVEL_* level.
+    // Contract: Never throws; msg is a non-owning view.
     void (DNG_ABI_CALL *log)(void* user, dng_u32 level, dng_str_view_v1 msg);
 
     // Purpose : Allocate memory using host allocator.
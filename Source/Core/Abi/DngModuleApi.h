@@ -15,9 +15,60 @@ extern "C" {
 #endif
 
 #include "DngAbi.h"
+#include "DngAudioApi.h"
 #include "DngHostApi.h"
 #include "DngWindowApi.h"
 
+// API families a module can export (dng_module_info.api_families bits).
+#define DNG_API_FAMILY_WINDOW ((dng_u64)1u << 0)
+#define DNG_API_FAMILY_AUDIO  ((dng_u64)1u << 1)
+
+// Purpose : Module description returned by dngModuleQueryInfo.
+// Contract: Filled entirely by the module (header included); module_name
+//           points to static storage and stays valid while the module is
+//           loaded. The module supports every ABI version in
+//           [min_abi_version, max_abi_version].
+typedef struct dng_module_info {
+    dng_abi_header_v1 header; // { sizeof(dng_module_info), DNG_ABI_VERSION_V1 }
+
+    dng_str_view_v1 module_name;
+    dng_u32         module_version_major;
+    dng_u32         module_version_minor;
+    dng_u32         module_version_patch;
+    dng_u32         min_abi_version;
+    dng_u32         max_abi_version;
+    dng_u64         api_families; // DNG_API_FAMILY_* bits.
+} dng_module_info;
+
+// Purpose : Describe the module before any host API exists.
+// Contract: Optional export; modules without it only provide ABI v1 and the
+//           window family. Needs no host services and allocates nothing;
+//           out_info non-null.
+DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngModuleQueryInfo(dng_module_info* out_info);
+
+// Purpose : Struct sizes a module uses for one ABI version (layout checks and
+//           ABI diff tooling).
+// Contract: A size of 0 means the struct does not exist in that version.
+//           Filled entirely by the module; header.abi_version is the queried
+//           version.
+typedef struct dng_abi_struct_sizes_v1 {
+    dng_abi_header_v1 header; // { sizeof(dng_abi_struct_sizes_v1), abi_version }
+
+    dng_u32 host_api;
+    dng_u32 module_api;
+    dng_u32 window_api;
+    dng_u32 window_desc;
+    dng_u32 window_event;
+    dng_u32 module_info;
+    dng_u32 audio_api;
+} dng_abi_struct_sizes_v1;
+
+// Purpose : Report struct sizes for abi_version.
+// Contract: Optional export; needs no host services; out_sizes non-null;
+//           versions outside [min_abi_version, max_abi_version] return
+//           DNG_STATUS_UNSUPPORTED.
+DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngModuleQueryStructSizes(dng_u32 abi_version, dng_abi_struct_sizes_v1* out_sizes);
+
 typedef struct dng_module_api_v1 {
     dng_abi_header_v1 header; // { struct_size, abi_version }
 
@@ -39,10 +90,42 @@ typedef struct dng_module_api_v1 {
     dng_status_v1 (DNG_ABI_CALL *shutdown)(void* ctx, const dng_host_api_v1* host);
 } dng_module_api_v1;
 
+// Contract: The host zeroes out_api and sets out_api->header and
+//           out_api->window.header to the v1 sizes; the module never writes
+//           past the declared sizes and rejects smaller ones.
 DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngModuleGetApi_v1(
     const dng_host_api_v1* host,
     dng_module_api_v1* out_api);
 
+// Purpose : Extended module table (ABI v2). Same leading fields as v1; the
+//           window table is the dng_window_api_v2 superset and the audio
+//           sub-API follows shutdown.
+// Contract: Returned by dngModuleGetApi_v2 only. The table is frozen once
+//           published; later additions need a new version.
+typedef struct dng_module_api_v2 {
+    dng_abi_header_v1 header; // { struct_size, abi_version = DNG_ABI_VERSION_V2 }
+
+    dng_str_view_v1 module_name;
+    dng_u32         module_version_major;
+    dng_u32         module_version_minor;
+    dng_u32         module_version_patch;
+
+    dng_window_api_v2 window;
+
+    // Same contract as dng_module_api_v1.shutdown.
+    dng_status_v1 (DNG_ABI_CALL *shutdown)(void* ctx, const dng_host_api_v1* host);
+
+    dng_audio_api_v1 audio; // Released by shutdown together with window.ctx.
+} dng_module_api_v2;
+
+// Purpose : Optional v2 entrypoint; modules without it only provide v1.
+// Contract: host stays a dng_host_api_v1. The host zeroes out_api and sets
+//           every embedded header to the v2 sizes; the module never writes
+//           past the declared sizes and rejects smaller ones.
+DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngModuleGetApi_v2(
+    const dng_host_api_v1* host,
+    dng_module_api_v2* out_api);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
@@ -18,11 +18,36 @@ extern "C" {
 
 typedef dng_u64 dng_window_handle_v1; // 0 is invalid.
 
+// Window handles pack a 32-bit slot index (low bits) with a 32-bit generation
+// (high bits). Generations start at 1, so a valid handle is never 0. Hosts
+// only need these to log or key handles; modules validate them on every call.
+static inline dng_u32 dng_handle_slot(dng_window_handle_v1 handle)
+{
+    return (dng_u32)(handle & 0xFFFFFFFFu);
+}
+
+static inline dng_u32 dng_handle_generation(dng_window_handle_v1 handle)
+{
+    return (dng_u32)(handle >> 32);
+}
+
+static inline dng_window_handle_v1 dng_make_handle(dng_u32 slot, dng_u32 generation)
+{
+    return ((dng_window_handle_v1)generation << 32) | (dng_window_handle_v1)slot;
+}
+
+// Window creation flags (dng_window_desc_v1.flags). Modules reject bits they
+// do not define with DNG_STATUS_UNSUPPORTED.
+#define DNG_WINDOW_FLAG_RESIZABLE  ((dng_u32)(1u << 0))
+#define DNG_WINDOW_FLAG_BORDERLESS ((dng_u32)(1u << 1))
+#define DNG_WINDOW_FLAG_HIDDEN     ((dng_u32)(1u << 2))
+#define DNG_WINDOW_FLAG_FULLSCREEN ((dng_u32)(1u << 3)) // Size follows the display.
+
 typedef struct dng_window_desc_v1 {
     dng_u32         width;
     dng_u32         height;
     dng_str_view_v1 title;  // Non-owning view.
-    dng_u32         flags;  // Reserved for future expansion; must be 0 for v1 (reject non-zero).
+    dng_u32         flags;  // DNG_WINDOW_FLAG_* bits; 0 for a plain window.
 } dng_window_desc_v1;
 
 typedef struct dng_window_size_v1 {
@@ -30,6 +55,14 @@ typedef struct dng_window_size_v1 {
     dng_u32 height;
 } dng_window_size_v1;
 
+// Pixel rectangle on a window surface; zero width or height means empty.
+typedef struct dng_rect_v1 {
+    dng_i32 x;
+    dng_i32 y;
+    dng_u32 width;
+    dng_u32 height;
+} dng_rect_v1;
+
 typedef struct dng_window_api_v1 {
     dng_abi_header_v1 header; // { struct_size, abi_version }
     void*             ctx;    // Module-owned context; host must not mutate.
@@ -41,6 +74,243 @@ typedef struct dng_window_api_v1 {
     dng_status_v1 (DNG_ABI_CALL *set_title)(void* ctx, dng_window_handle_v1 handle, dng_str_view_v1 title);
 } dng_window_api_v1;
 
+// Kind of pointer device that produced a window's most recent pointer input.
+typedef dng_u32 dng_pointer_device_kind_v1;
+#define DNG_PTR_MOUSE  ((dng_pointer_device_kind_v1)0u)
+#define DNG_PTR_TOUCH  ((dng_pointer_device_kind_v1)1u)
+#define DNG_PTR_STYLUS ((dng_pointer_device_kind_v1)2u)
+
+// Media key bits reported by get_media_key_state (set while held).
+typedef dng_u32 dng_media_key_v1;
+#define DNG_MEDIA_KEY_PLAY  ((dng_media_key_v1)(1u << 0))
+#define DNG_MEDIA_KEY_PAUSE ((dng_media_key_v1)(1u << 1))
+#define DNG_MEDIA_KEY_NEXT  ((dng_media_key_v1)(1u << 2))
+#define DNG_MEDIA_KEY_PREV  ((dng_media_key_v1)(1u << 3))
+
+// Window event types delivered by poll_event.
+typedef dng_u32 dng_window_event_type_v1;
+#define DNG_EVENT_NONE   ((dng_window_event_type_v1)0u)
+#define DNG_EVENT_RESIZE ((dng_window_event_type_v1)1u) // payload.size
+#define DNG_EVENT_CLOSE  ((dng_window_event_type_v1)2u) // No payload.
+#define DNG_EVENT_FOCUS  ((dng_window_event_type_v1)3u) // payload.focus
+#define DNG_EVENT_IPC    ((dng_window_event_type_v1)4u) // payload.ipc
+// Module-scope event (handle 0): the session is ending and the host should save and quit.
+#define DNG_EVENT_QUIT_REQUESTED ((dng_window_event_type_v1)5u) // No payload.
+// Pointer events carry payload.pointer in window surface coordinates.
+#define DNG_EVENT_TOUCH_DOWN ((dng_window_event_type_v1)6u)
+#define DNG_EVENT_TOUCH_MOVE ((dng_window_event_type_v1)7u)
+#define DNG_EVENT_TOUCH_UP   ((dng_window_event_type_v1)8u)
+#define DNG_EVENT_MOUSE_DOWN ((dng_window_event_type_v1)9u)
+#define DNG_EVENT_MOUSE_MOVE ((dng_window_event_type_v1)10u)
+#define DNG_EVENT_MOUSE_UP   ((dng_window_event_type_v1)11u)
+
+typedef struct dng_window_focus_v1 {
+    dng_bool_v1 focused;
+} dng_window_focus_v1;
+
+typedef struct dng_window_pointer_v1 {
+    dng_i32 x;
+    dng_i32 y;
+    dng_u32 touch_id; // Finger id for touch events; 0 for mouse events.
+} dng_window_pointer_v1;
+
+// IPC payloads are copied into the event itself, so they are capped.
+#define DNG_IPC_PAYLOAD_MAX 16u
+
+typedef struct dng_window_ipc_v1 {
+    dng_window_handle_v1 source; // Sending window.
+    dng_u32              kind;   // Application-defined message kind.
+    dng_u32              size;   // Valid bytes in data.
+    dng_u8               data[DNG_IPC_PAYLOAD_MAX];
+} dng_window_ipc_v1;
+
+// Payload selected by event_type; reserved fixes the union size at 32 bytes.
+typedef union dng_window_event_payload_v1 {
+    dng_window_size_v1    size;
+    dng_window_focus_v1   focus;
+    dng_window_ipc_v1     ipc;
+    dng_window_pointer_v1 pointer;
+    dng_u64               reserved[4];
+} dng_window_event_payload_v1;
+
+typedef struct dng_window_event_v1 {
+    dng_window_event_type_v1    event_type;
+    dng_window_handle_v1        handle;    // Window the event belongs to.
+    dng_u64                     timestamp; // Engine ticks when queued (see set_time_base).
+    dng_window_event_payload_v1 payload;
+} dng_window_event_v1;
+
+// Purpose : Checked payload accessors exported by window modules.
+// Contract: Copy the payload into the out parameter only when event_type
+//           selects it; a NULL pointer or another event type returns
+//           DNG_STATUS_INVALID_ARG. dngEventGetPointer accepts every
+//           DNG_EVENT_TOUCH_* and DNG_EVENT_MOUSE_* type.
+DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngEventGetResize(const dng_window_event_v1* event, dng_window_size_v1* out_size);
+DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngEventGetFocus(const dng_window_event_v1* event, dng_window_focus_v1* out_focus);
+DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngEventGetIpc(const dng_window_event_v1* event, dng_window_ipc_v1* out_ipc);
+DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngEventGetPointer(const dng_window_event_v1* event, dng_window_pointer_v1* out_pointer);
+
+// Screen reader announcement urgency.
+typedef dng_u32 dng_announce_priority_v1;
+#define DNG_ANNOUNCE_POLITE    ((dng_announce_priority_v1)0u) // Wait for current speech.
+#define DNG_ANNOUNCE_ASSERTIVE ((dng_announce_priority_v1)1u) // Interrupt current speech.
+
+// Renderer kinds reported by get_renderer_info.
+#define DNG_RENDERER_NONE   0u
+#define DNG_RENDERER_VULKAN 1u
+#define DNG_RENDERER_METAL  2u
+#define DNG_RENDERER_D3D12  3u
+
+// Renderer attached to a window; vendor/device ids are PCI ids, 0 when unknown.
+typedef struct dng_renderer_info_v1 {
+    dng_u32 kind;        // DNG_RENDERER_*
+    dng_u32 api_version; // Backend API version; 0 for DNG_RENDERER_NONE.
+    dng_u32 vendor_id;
+    dng_u32 device_id;
+} dng_renderer_info_v1;
+
+// Purpose : Window table returned through dng_module_api_v2.
+// Contract: Starts with exactly the dng_window_api_v1 fields (header.abi_version
+//           is DNG_ABI_VERSION_V2); extension callbacks follow in order.
+typedef struct dng_window_api_v2 {
+    dng_abi_header_v1 header; // { struct_size, abi_version }
+    void*             ctx;    // Module-owned context; host must not mutate.
+
+    dng_status_v1 (DNG_ABI_CALL *create)(void* ctx, const dng_window_desc_v1* desc, dng_window_handle_v1* out_handle);
+    dng_status_v1 (DNG_ABI_CALL *destroy)(void* ctx, dng_window_handle_v1 handle);
+    dng_status_v1 (DNG_ABI_CALL *poll)(void* ctx);
+    dng_status_v1 (DNG_ABI_CALL *get_size)(void* ctx, dng_window_handle_v1 handle, dng_window_size_v1* out_size);
+    dng_status_v1 (DNG_ABI_CALL *set_title)(void* ctx, dng_window_handle_v1 handle, dng_str_view_v1 title);
+
+    // Purpose : Hint which thread should fire frame callbacks for a window.
+    // Contract: thread_id is host-defined; 0 means the calling thread.
+    dng_status_v1 (DNG_ABI_CALL *set_frame_callback_thread)(void* ctx, dng_window_handle_v1 handle, dng_u64 thread_id);
+
+    // Purpose : Read back the frame callback thread hint for a window.
+    // Contract: out_thread_id non-null; reports 0 until a hint is set.
+    dng_status_v1 (DNG_ABI_CALL *get_frame_callback_thread)(void* ctx, dng_window_handle_v1 handle, dng_u64* out_thread_id);
+
+    // Purpose : Copy the stored title bytes (no terminator) into buffer.
+    // Contract: out_size non-null and always receives the title size; buffer
+    //           NULL is a size query; capacity < size returns INVALID_ARG.
+    dng_status_v1 (DNG_ABI_CALL *get_title)(void* ctx, dng_window_handle_v1 handle, char* buffer, dng_u32 capacity, dng_u32* out_size);
+
+    // Purpose : Report the device kind of the last pointer input on a window.
+    // Contract: out_kind non-null; DNG_PTR_MOUSE until pointer input arrives.
+    dng_status_v1 (DNG_ABI_CALL *get_pointer_device_kind)(void* ctx, dng_window_handle_v1 handle, dng_pointer_device_kind_v1* out_kind);
+
+    // Purpose : Resize a window's client area.
+    // Contract: size non-null with non-zero width and height; get_size reports
+    //           the new size immediately.
+    dng_status_v1 (DNG_ABI_CALL *set_size)(void* ctx, dng_window_handle_v1 handle, const dng_window_size_v1* size);
+
+    // Purpose : Report which media keys are currently held (module-wide).
+    // Contract: out_keys non-null; receives a mask of DNG_MEDIA_KEY_* bits.
+    dng_status_v1 (DNG_ABI_CALL *get_media_key_state)(void* ctx, dng_u32* out_keys);
+
+    // Purpose : Pop the oldest pending window event (FIFO).
+    // Contract: out_event non-null; returns DNG_STATUS_EMPTY when no event is
+    //           pending and leaves out_event untouched.
+    dng_status_v1 (DNG_ABI_CALL *poll_event)(void* ctx, dng_window_event_v1* out_event);
+
+    // Purpose : Ask assistive technology to speak text for a window.
+    // Contract: text is copied and must be UTF-8 (DNG_STATUS_INVALID_UTF8) of at
+    //           most 64 KiB; unknown priorities return DNG_STATUS_INVALID_ARG.
+    dng_status_v1 (DNG_ABI_CALL *announce)(void* ctx, dng_window_handle_v1 handle, dng_str_view_v1 text, dng_announce_priority_v1 priority);
+
+    // Purpose : Set the task switcher description, independent of the title.
+    // Contract: desc is copied and validated like a title (UTF-8, at most
+    //           64 KiB); size 0 clears. On failure the previous description
+    //           is kept.
+    dng_status_v1 (DNG_ABI_CALL *set_window_task_description)(void* ctx, dng_window_handle_v1 handle, dng_str_view_v1 desc);
+
+    // Purpose : Copy the task description; same buffer rules as get_title.
+    dng_status_v1 (DNG_ABI_CALL *get_window_task_description)(void* ctx, dng_window_handle_v1 handle, char* buffer, dng_u32 capacity, dng_u32* out_size);
+
+    // Purpose : Report the DNG_WINDOW_FLAG_* bits a window was created with.
+    // Contract: out_flags non-null.
+    dng_status_v1 (DNG_ABI_CALL *get_flags)(void* ctx, dng_window_handle_v1 handle, dng_u32* out_flags);
+
+    // Purpose : Attach a window to a parent window (0 makes it top-level).
+    // Contract: parent must be live; a parent that would form a cycle returns
+    //           DNG_STATUS_INVALID_ARG.
+    dng_status_v1 (DNG_ABI_CALL *set_parent)(void* ctx, dng_window_handle_v1 handle, dng_window_handle_v1 parent);
+
+    // Purpose : Copy a window's ancestors, nearest parent first.
+    // Contract: out_depth non-null and always receives the ancestor count;
+    //           buffer NULL is a depth query; max_depth < depth returns
+    //           DNG_STATUS_INVALID_ARG and leaves buffer untouched.
+    dng_status_v1 (DNG_ABI_CALL *get_ancestor_chain)(void* ctx, dng_window_handle_v1 handle, dng_window_handle_v1* buffer, dng_u32 max_depth, dng_u32* out_depth);
+
+    // Purpose : Report how many ancestors a window has (0 = top-level).
+    // Contract: out_depth non-null.
+    dng_status_v1 (DNG_ABI_CALL *get_window_depth)(void* ctx, dng_window_handle_v1 handle, dng_u32* out_depth);
+
+    // Purpose : Queue a DNG_EVENT_IPC event for dst with a copy of payload.
+    // Contract: size <= DNG_IPC_PAYLOAD_MAX; size > 0 requires non-NULL payload;
+    //           returns DNG_STATUS_NOT_FOUND when src or dst is not live.
+    dng_status_v1 (DNG_ABI_CALL *send_ipc_message)(void* ctx, dng_window_handle_v1 src, dng_window_handle_v1 dst, dng_u32 kind, const void* payload, dng_u32 size);
+
+    // Purpose : Add a rectangle to the window's dirty region.
+    // Contract: rect is clamped to the window surface; empty results are ignored.
+    dng_status_v1 (DNG_ABI_CALL *mark_dirty)(void* ctx, dng_window_handle_v1 handle, dng_rect_v1 rect);
+
+    // Purpose : Return the bounding box of the dirty region and reset it.
+    // Contract: out_rect non-null; returns DNG_STATUS_EMPTY with an empty rect
+    //           when nothing is dirty. New and resized windows are fully dirty.
+    dng_status_v1 (DNG_ABI_CALL *get_and_clear_dirty)(void* ctx, dng_window_handle_v1 handle, dng_rect_v1* out_rect);
+
+    // Purpose : Ask the host to quit (sticky until cancel_quit).
+    // Contract: Queues one DNG_EVENT_QUIT_REQUESTED (handle 0) when the flag
+    //           goes from clear to set; repeated requests queue nothing.
+    dng_status_v1 (DNG_ABI_CALL *request_quit)(void* ctx);
+
+    // Purpose : Report whether a quit request is pending.
+    // Contract: out_requested non-null; DNG_BOOL_TRUE until cancel_quit.
+    dng_status_v1 (DNG_ABI_CALL *get_quit_requested)(void* ctx, dng_bool_v1* out_requested);
+
+    // Purpose : Clear a pending quit request (e.g. the user chose to stay).
+    dng_status_v1 (DNG_ABI_CALL *cancel_quit)(void* ctx);
+
+    // Purpose : Mirror touch input of the first finger down as mouse events.
+    // Contract: When enabled, each such touch event is followed by the
+    //           matching DNG_EVENT_MOUSE_* event (touch_id 0).
+    dng_status_v1 (DNG_ABI_CALL *set_touch_to_mouse_emulation)(void* ctx, dng_window_handle_v1 handle, dng_bool_v1 enabled);
+
+    // Purpose : Define the engine clock relative to host time (nanoseconds).
+    // Contract: engine_ticks_per_second > 0. Default: host nanoseconds from 0.
+    //           Events are stamped when queued; already queued events keep
+    //           their stamps.
+    dng_status_v1 (DNG_ABI_CALL *set_time_base)(void* ctx, dng_u64 host_time_at_engine_zero, dng_u64 engine_ticks_per_second);
+
+    // Purpose : Convert a host timestamp (nanoseconds) to engine ticks.
+    // Contract: out_ticks non-null; rounds down. Times before engine zero or
+    //           past the dng_u64 tick range return DNG_STATUS_INVALID_ARG.
+    dng_status_v1 (DNG_ABI_CALL *convert_time)(void* ctx, dng_u64 host_time_ns, dng_u64* out_ticks);
+
+    // Purpose : Report which renderer backs a window.
+    // Contract: out_info non-null.
+    dng_status_v1 (DNG_ABI_CALL *get_renderer_info)(void* ctx, dng_window_handle_v1 handle, dng_renderer_info_v1* out_info);
+
+    // Purpose : Store a preferred aspect ratio for a window (never enforced).
+    // Contract: 0, 0 clears the hint; a single zero term returns
+    //           DNG_STATUS_INVALID_ARG.
+    dng_status_v1 (DNG_ABI_CALL *set_window_aspect_hint)(void* ctx, dng_window_handle_v1 handle, dng_u32 numer, dng_u32 denom);
+
+    // Purpose : Read back the aspect ratio hint (0, 0 when none is set).
+    // Contract: out_numer and out_denom non-null.
+    dng_status_v1 (DNG_ABI_CALL *get_window_aspect_hint)(void* ctx, dng_window_handle_v1 handle, dng_u32* out_numer, dng_u32* out_denom);
+
+    // Purpose : Report whether a window is the main window.
+    // Contract: out_is_main non-null.
+    dng_status_v1 (DNG_ABI_CALL *get_is_main_window)(void* ctx, dng_window_handle_v1 handle, dng_bool_v1* out_is_main);
+
+    // Purpose : Make a window the single main window, or clear the flag.
+    // Contract: DNG_BOOL_TRUE demotes the previous main window; DNG_BOOL_FALSE
+    //           only clears the flag on the current main window.
+    dng_status_v1 (DNG_ABI_CALL *set_is_main_window)(void* ctx, dng_window_handle_v1 handle, dng_bool_v1 is_main);
+} dng_window_api_v2;
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
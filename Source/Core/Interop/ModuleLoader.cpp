@@ -5,7 +5,9 @@
 // Contract: No exceptions/RTTI; C ABI entrypoint; returns status codes only;
 //           cold-path usage; ASCII-only logging via host log callback.
 // Notes   : Caller manages thread-safety. Loader owns the module handle until
-//           Unload. ABI v1 entrypoint name is dngModuleGetApi_v1.
+//           Unload. ABI v1 entrypoint name is dngModuleGetApi_v1; modules that
+//           export dngModuleQueryInfo are checked for v1 and window support
+//           before it is called.
 // ============================================================================
 #include "Core/Interop/ModuleLoader.hpp"
 
@@ -42,7 +44,7 @@ namespace
         dng_str_view_v1 view;
         view.data = message;
         view.size = StrLen32(message);
-        host->log(host->user, 1u, view);
+        host->log(host->user, DNG_LOG_LEVEL_ERROR, view);
     }
 
     static dng_status_v1 ValidateStrView(const dng_str_view_v1& view, const char* label, const dng_host_api_v1* host) noexcept
@@ -150,6 +152,40 @@ namespace
         return ValidateWindowApiV1(&api->window, host);
     }
 
+    typedef dng_status_v1 (DNG_ABI_CALL *QueryInfoFn)(dng_module_info*);
+
+    // Modules predating dngModuleQueryInfo (query_info NULL) are v1 window modules.
+    static dng_status_v1 CheckModuleInfoV1(QueryInfoFn query_info, const dng_host_api_v1* host) noexcept
+    {
+        if (!query_info)
+        {
+            return DNG_STATUS_OK;
+        }
+
+        dng_module_info info;
+        ::memset(&info, 0, sizeof(info));
+        const dng_status_v1 status = query_info(&info);
+        if (status != DNG_STATUS_OK)
+        {
+            LogIssue(host, "dngModuleQueryInfo failed");
+            return status;
+        }
+
+        if (info.min_abi_version > DNG_ABI_VERSION_V1 || info.max_abi_version < DNG_ABI_VERSION_V1)
+        {
+            LogIssue(host, "Module does not support ABI v1");
+            return DNG_STATUS_UNSUPPORTED;
+        }
+
+        if ((info.api_families & DNG_API_FAMILY_WINDOW) == 0u)
+        {
+            LogIssue(host, "Module does not export the window API");
+            return DNG_STATUS_UNSUPPORTED;
+        }
+
+        return ValidateStrView(info.module_name, "ModuleInfo module_name invalid", host);
+    }
+
 #if defined(_WIN32) || defined(_WIN64)
     static void LogWin32Error(const dng_host_api_v1* host, dng_u32 level, const char* prefix) noexcept
     {
@@ -214,7 +250,7 @@ dng_status_v1 ModuleLoader::Load(const char* path, const dng_host_api_v1* host,
     HMODULE lib = ::LoadLibraryA(path);
     if (!lib)
     {
-        LogWin32Error(host, 1u, "LoadLibraryA failed");
+        LogWin32Error(host, DNG_LOG_LEVEL_ERROR, "LoadLibraryA failed");
         return DNG_STATUS_FAIL;
     }
 
@@ -227,19 +263,20 @@ dng_status_v1 ModuleLoader::Load(const char* path, const dng_host_api_v1* host,
 
     if (!proc)
     {
-        Log(host, 1u, "dngModuleGetApi_v1 not found");
+        Log(host, DNG_LOG_LEVEL_ERROR, "dngModuleGetApi_v1 not found");
         ::FreeLibrary(lib);
         return DNG_STATUS_UNSUPPORTED;
     }
 
     auto entry = reinterpret_cast<dng_status_v1 (DNG_ABI_CALL *)(const dng_host_api_v1*, dng_module_api_v1*)>(proc);
+    auto query_info = reinterpret_cast<QueryInfoFn>(::GetProcAddress(lib, "dngModuleQueryInfo"));
 #else
     // RTLD_NOW resolves relocations at load time; RTLD_LOCAL avoids exporting symbols globally.
     void* lib = ::dlopen(path, RTLD_NOW | RTLD_LOCAL);
     if (!lib)
     {
         const char* err = ::dlerror();
-        Log(host, 1u, err ? err : "dlopen failed");
+        Log(host, DNG_LOG_LEVEL_ERROR, err ? err : "dlopen failed");
         return DNG_STATUS_FAIL;
     }
 
@@ -249,19 +286,36 @@ dng_status_v1 ModuleLoader::Load(const char* path, const dng_host_api_v1* host,
     const char* sym_err = ::dlerror();
     if (sym_err != nullptr)
     {
-        Log(host, 1u, sym_err);
+        Log(host, DNG_LOG_LEVEL_ERROR, sym_err);
         ::dlclose(lib);
         return DNG_STATUS_UNSUPPORTED;
     }
     if (!sym)
     {
-        Log(host, 1u, "dlsym returned null for dngModuleGetApi_v1");
+        Log(host, DNG_LOG_LEVEL_ERROR, "dlsym returned null for dngModuleGetApi_v1");
         ::dlclose(lib);
         return DNG_STATUS_UNSUPPORTED;
     }
 
     auto entry = reinterpret_cast<dng_status_v1 (DNG_ABI_CALL *)(const dng_host_api_v1*, dng_module_api_v1*)>(sym);
+
+    // dngModuleQueryInfo is optional; a missing symbol leaves query_info NULL.
+    (void)::dlerror();
+    void* query_sym = ::dlsym(lib, "dngModuleQueryInfo");
+    auto query_info = ::dlerror() == nullptr ? reinterpret_cast<QueryInfoFn>(query_sym) : nullptr;
+#endif
+
+    // Negotiate before handing the module any host services.
+    const dng_status_v1 info_ok = CheckModuleInfoV1(query_info, host);
+    if (info_ok != DNG_STATUS_OK)
+    {
+#if defined(_WIN32) || defined(_WIN64)
+        ::FreeLibrary(lib);
+#else
+        ::dlclose(lib);
 #endif
+        return info_ok;
+    }
 
     // Zero output before fill and provide header defaults (caller-owned size/version handshake).
     ::memset(outApi, 0, sizeof(*outApi));
@@ -284,7 +338,7 @@ dng_status_v1 ModuleLoader::Load(const char* path, const dng_host_api_v1* host,
     const dng_status_v1 api_ok = ValidateModuleApiV1(outApi, host);
     if (api_ok != DNG_STATUS_OK)
     {
-        Log(host, 1u, "Module returned an invalid API table");
+        Log(host, DNG_LOG_LEVEL_ERROR, "Module returned an invalid API table");
 #if defined(_WIN32) || defined(_WIN64)
         ::FreeLibrary(lib);
 #else
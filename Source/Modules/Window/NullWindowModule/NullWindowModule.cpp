@@ -6,6 +6,8 @@
 //           context allocated via host->alloc in dngModuleGetApi_v1 and freed
 //           in shutdown; host allocator used for title copies; caller must call
 //           shutdown exactly once before module unload.
+//           Accepts the DNG_WINDOW_FLAG_* bits; fullscreen windows take the
+//           1920x1080 virtual display size.
 // Notes   : set_title allocates via host->alloc and frees previous via host->free;
 //           not intended for hot paths. Determinism follows host pump cadence.
 //           Context is thread-safe per-instance (one context per module load).
@@ -23,6 +25,12 @@ typedef struct NullWindowCtx {
     dng_u32                title_size;
 } NullWindowCtx;
 
+// Flags this module understands and the virtual display used for fullscreen.
+static const dng_u32 kSupportedWindowFlags = DNG_WINDOW_FLAG_RESIZABLE | DNG_WINDOW_FLAG_BORDERLESS |
+                                             DNG_WINDOW_FLAG_HIDDEN | DNG_WINDOW_FLAG_FULLSCREEN;
+static const dng_u32 kVirtualDisplayWidth = 1920u;
+static const dng_u32 kVirtualDisplayHeight = 1080u;
+
 // Context and title allocation constants (size and align must match free).
 static const dng_u64 kNullWindowCtxSize = sizeof(NullWindowCtx);
 static const dng_u64 kNullWindowCtxAlign = alignof(NullWindowCtx);
@@ -81,17 +89,25 @@ static dng_status_v1 DNG_ABI_CALL NullWindow_Create(void* raw_ctx, const dng_win
     {
         return DNG_STATUS_INVALID_ARG;
     }
-    if (desc->flags != 0u)
+    if ((desc->flags & ~kSupportedWindowFlags) != 0u)
     {
-        return DNG_STATUS_INVALID_ARG;
+        return DNG_STATUS_UNSUPPORTED;
     }
     if (ctx->handle != 0u)
     {
         return DNG_STATUS_FAIL; // Only one window supported.
     }
 
-    ctx->size.width = desc->width;
-    ctx->size.height = desc->height;
+    if ((desc->flags & DNG_WINDOW_FLAG_FULLSCREEN) != 0u)
+    {
+        ctx->size.width = kVirtualDisplayWidth;
+        ctx->size.height = kVirtualDisplayHeight;
+    }
+    else
+    {
+        ctx->size.width = desc->width;
+        ctx->size.height = desc->height;
+    }
 
     const dng_status_v1 title_status = NullWindow_SetTitleInternal(ctx, desc->title);
     if (title_status != DNG_STATUS_OK)
@@ -203,6 +219,27 @@ static void NullWindow_FillModuleApi(NullWindowCtx* ctx, dng_module_api_v1* api)
     NullWindow_InitWindowApi(ctx, &api->window);
 }
 
+DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngModuleQueryInfo(dng_module_info* out_info)
+{
+    if (!out_info)
+    {
+        return DNG_STATUS_INVALID_ARG;
+    }
+
+    memset(out_info, 0, sizeof(*out_info));
+    out_info->header.struct_size = (dng_u32)sizeof(dng_module_info);
+    out_info->header.abi_version = DNG_ABI_VERSION_V1;
+    out_info->module_name.data = "NullWindowModule";
+    out_info->module_name.size = NullWindow_StrLen(out_info->module_name.data);
+    out_info->module_version_major = 1u;
+    out_info->module_version_minor = 0u;
+    out_info->module_version_patch = 0u;
+    out_info->min_abi_version = DNG_ABI_VERSION_V1;
+    out_info->max_abi_version = DNG_ABI_VERSION_V1;
+    out_info->api_families = DNG_API_FAMILY_WINDOW;
+    return DNG_STATUS_OK;
+}
+
 DNG_ABI_API dng_status_v1 DNG_ABI_CALL dngModuleGetApi_v1(const dng_host_api_v1* host, dng_module_api_v1* out_api)
 {
     if (!host || !out_api || !host->alloc || !host->free)
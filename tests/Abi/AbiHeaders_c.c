@@ -1,5 +1,6 @@
 // Compile-only C99 check for ABI headers.
 #include "Core/Abi/DngAbi.h"
+#include "Core/Abi/DngAudioApi.h"
 #include "Core/Abi/DngHostApi.h"
 #include "Core/Abi/DngModuleApi.h"
 #include "Core/Abi/DngWindowApi.h"
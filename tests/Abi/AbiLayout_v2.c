@@ -0,0 +1,78 @@
+// ABI layout enforcement for v2 (C11): v2 tables must keep the v1 prefix.
+#include <assert.h>
+#include <stddef.h>
+#include <string.h>
+
+#include "Core/Abi/DngAbi.h"
+#include "Core/Abi/DngAudioApi.h"
+#include "Core/Abi/DngHostApi.h"
+#include "Core/Abi/DngModuleApi.h"
+#include "Core/Abi/DngWindowApi.h"
+
+// dng_window_api_v2 starts with every dng_window_api_v1 field at the same offset.
+static_assert(offsetof(dng_window_api_v2, header) == offsetof(dng_window_api_v1, header), "dng_window_api_v2.header offset");
+static_assert(offsetof(dng_window_api_v2, ctx) == offsetof(dng_window_api_v1, ctx), "dng_window_api_v2.ctx offset");
+static_assert(offsetof(dng_window_api_v2, create) == offsetof(dng_window_api_v1, create), "dng_window_api_v2.create offset");
+static_assert(offsetof(dng_window_api_v2, destroy) == offsetof(dng_window_api_v1, destroy), "dng_window_api_v2.destroy offset");
+static_assert(offsetof(dng_window_api_v2, poll) == offsetof(dng_window_api_v1, poll), "dng_window_api_v2.poll offset");
+static_assert(offsetof(dng_window_api_v2, get_size) == offsetof(dng_window_api_v1, get_size), "dng_window_api_v2.get_size offset");
+static_assert(offsetof(dng_window_api_v2, set_title) == offsetof(dng_window_api_v1, set_title), "dng_window_api_v2.set_title offset");
+static_assert(offsetof(dng_window_api_v2, set_frame_callback_thread) == sizeof(dng_window_api_v1), "v2 extensions follow the v1 prefix");
+static_assert(_Alignof(dng_window_api_v2) == _Alignof(void*), "dng_window_api_v2 align");
+
+// dng_module_api_v2 keeps the v1 metadata fields and window offset.
+static_assert(offsetof(dng_module_api_v2, header) == 0u, "dng_module_api_v2.header offset");
+static_assert(offsetof(dng_module_api_v2, module_name) == offsetof(dng_module_api_v1, module_name), "dng_module_api_v2.module_name offset");
+static_assert(offsetof(dng_module_api_v2, module_version_patch) == offsetof(dng_module_api_v1, module_version_patch), "dng_module_api_v2.module_version_patch offset");
+static_assert(offsetof(dng_module_api_v2, window) == offsetof(dng_module_api_v1, window), "dng_module_api_v2.window offset");
+static_assert(offsetof(dng_module_api_v2, shutdown) == offsetof(dng_module_api_v2, window) + sizeof(dng_window_api_v2), "dng_module_api_v2.shutdown offset");
+static_assert(offsetof(dng_module_api_v2, audio) == offsetof(dng_module_api_v2, shutdown) + sizeof(void*), "dng_module_api_v2.audio offset");
+static_assert(sizeof(dng_module_api_v2) == offsetof(dng_module_api_v2, audio) + sizeof(dng_audio_api_v1), "dng_module_api_v2 ends with audio");
+static_assert(_Alignof(dng_module_api_v2) == _Alignof(void*), "dng_module_api_v2 align");
+
+// dng_audio_api_v1 follows the usual { header, ctx, callbacks } table shape.
+static_assert(offsetof(dng_audio_api_v1, ctx) == offsetof(dng_window_api_v1, ctx), "dng_audio_api_v1.ctx offset");
+static_assert(sizeof(dng_audio_api_v1) == offsetof(dng_audio_api_v1, ctx) + 6u * sizeof(void*), "dng_audio_api_v1 size");
+
+// dng_module_info starts like the module tables and ends with the 64-bit family mask.
+static_assert(offsetof(dng_module_info, header) == 0u, "dng_module_info.header offset");
+static_assert(offsetof(dng_module_info, module_name) == offsetof(dng_module_api_v1, module_name), "dng_module_info.module_name offset");
+static_assert(offsetof(dng_module_info, module_version_patch) == offsetof(dng_module_api_v1, module_version_patch), "dng_module_info.module_version_patch offset");
+static_assert(offsetof(dng_module_info, min_abi_version) == offsetof(dng_module_info, module_version_patch) + sizeof(dng_u32), "dng_module_info.min_abi_version offset");
+static_assert(offsetof(dng_module_info, max_abi_version) == offsetof(dng_module_info, min_abi_version) + sizeof(dng_u32), "dng_module_info.max_abi_version offset");
+static_assert(sizeof(dng_module_info) == offsetof(dng_module_info, api_families) + sizeof(dng_u64), "dng_module_info size");
+
+// dng_abi_struct_sizes_v1 is the header followed by seven packed dng_u32 sizes.
+static_assert(sizeof(dng_abi_struct_sizes_v1) == sizeof(dng_abi_header_v1) + 7u * sizeof(dng_u32), "dng_abi_struct_sizes_v1 size");
+static_assert(offsetof(dng_abi_struct_sizes_v1, host_api) == sizeof(dng_abi_header_v1), "dng_abi_struct_sizes_v1.host_api offset");
+static_assert(offsetof(dng_abi_struct_sizes_v1, audio_api) == sizeof(dng_abi_header_v1) + 6u * sizeof(dng_u32), "dng_abi_struct_sizes_v1.audio_api offset");
+
+// Event payloads share one fixed-size union so new event types keep the size.
+static_assert(sizeof(dng_window_event_payload_v1) == 32u, "dng_window_event_payload_v1 size");
+static_assert(offsetof(dng_window_event_v1, event_type) == 0u, "dng_window_event_v1.event_type offset");
+static_assert(offsetof(dng_window_event_v1, timestamp) == offsetof(dng_window_event_v1, handle) + sizeof(dng_u64), "dng_window_event_v1.timestamp offset");
+static_assert(offsetof(dng_window_event_v1, payload) == offsetof(dng_window_event_v1, timestamp) + sizeof(dng_u64), "dng_window_event_v1.payload offset");
+
+int AbiLayout_v2_CompileSentinel(void)
+{
+    // Hosts hand dngModuleGetApi_v2 a zeroed table with every header declared.
+    dng_module_api_v2 module_api;
+    memset(&module_api, 0, sizeof(module_api));
+    module_api.header.struct_size = (dng_u32)sizeof(dng_module_api_v2);
+    module_api.header.abi_version = DNG_ABI_VERSION_V2;
+    module_api.window.header.struct_size = (dng_u32)sizeof(dng_window_api_v2);
+    module_api.window.header.abi_version = DNG_ABI_VERSION_V2;
+    module_api.audio.header.struct_size = (dng_u32)sizeof(dng_audio_api_v1);
+    module_api.audio.header.abi_version = DNG_ABI_VERSION_V1;
+
+    int ok = 0;
+    ok |= (module_api.header.struct_size == sizeof(dng_module_api_v2)) ? 0 : 1;
+    ok |= (module_api.window.header.struct_size == sizeof(dng_window_api_v2)) ? 0 : 1;
+    ok |= (module_api.audio.header.struct_size == sizeof(dng_audio_api_v1)) ? 0 : 1;
+
+    // Handles round-trip through the C helpers: slot low, generation high.
+    const dng_window_handle_v1 handle = dng_make_handle(3u, 0xFFFFFFFFu);
+    ok |= (handle == 0xFFFFFFFF00000003ull) ? 0 : 1;
+    ok |= (dng_handle_slot(handle) == 3u && dng_handle_generation(handle) == 0xFFFFFFFFu) ? 0 : 1;
+    return ok;
+}
@@ -83,22 +83,44 @@ int main()
 
     dng_window_handle_v1 handle = 0u;
 
-    // Negative: v1 reserves flags; non-zero must be rejected.
+    // Negative: undefined flag bits must be rejected with UNSUPPORTED.
     dng_window_desc_v1 bad_desc = desc;
-    bad_desc.flags = 1u;
+    bad_desc.flags = 0x80000000u;
     dng_window_handle_v1 bad_handle = 0u;
     status = dng::WindowCreate(module_api.window, &bad_desc, &bad_handle);
-    if (status != DNG_STATUS_INVALID_ARG || bad_handle != 0u)
+    if (status != DNG_STATUS_UNSUPPORTED || bad_handle != 0u)
     {
         printf("Create with invalid flags did not fail as expected: %u\n", (unsigned)status);
         return 2;
     }
 
+    // Fullscreen windows report the virtual display size, not the requested one.
+    dng_window_desc_v1 fullscreen_desc = desc;
+    fullscreen_desc.flags = DNG_WINDOW_FLAG_FULLSCREEN | DNG_WINDOW_FLAG_BORDERLESS;
+    dng_window_handle_v1 fullscreen_handle = 0u;
+    dng_window_size_v1 fullscreen_size = {};
+    status = dng::WindowCreate(module_api.window, &fullscreen_desc, &fullscreen_handle);
+    if (status == DNG_STATUS_OK)
+    {
+        status = dng::WindowGetSize(module_api.window, fullscreen_handle, &fullscreen_size);
+    }
+    if (status != DNG_STATUS_OK || fullscreen_size.width != 1920u || fullscreen_size.height != 1080u)
+    {
+        printf("Fullscreen create/size failed: %u\n", (unsigned)status);
+        return 3;
+    }
+    status = dng::WindowDestroy(module_api.window, fullscreen_handle);
+    if (status != DNG_STATUS_OK)
+    {
+        printf("Fullscreen destroy failed: %u\n", (unsigned)status);
+        return 4;
+    }
+
     status = dng::WindowCreate(module_api.window, &desc, &handle);
     if (status != DNG_STATUS_OK || handle == 0u)
     {
         printf("Create failed: %u\n", (unsigned)status);
-        return 3;
+        return 5;
     }
 
     dng_window_size_v1 size = {};
@@ -106,7 +128,7 @@ int main()
     if (status != DNG_STATUS_OK || size.width != desc.width || size.height != desc.height)
     {
         printf("GetSize failed: %u\n", (unsigned)status);
-        return 4;
+        return 6;
     }
 
     dng_str_view_v1 new_title = { "Updated", 7u };
@@ -114,7 +136,7 @@ int main()
     if (status != DNG_STATUS_OK)
     {
         printf("SetTitle failed: %u\n", (unsigned)status);
-        return 5;
+        return 7;
     }
 
     // Negative: non-empty title requires non-null pointer.
@@ -123,21 +145,21 @@ int main()
     if (status != DNG_STATUS_INVALID_ARG)
     {
         printf("SetTitle with invalid view did not fail as expected: %u\n", (unsigned)status);
-        return 6;
+        return 8;
     }
 
     status = dng::WindowPoll(module_api.window);
     if (status != DNG_STATUS_OK)
     {
         printf("Poll failed: %u\n", (unsigned)status);
-        return 7;
+        return 9;
     }
 
     status = dng::WindowDestroy(module_api.window, handle);
     if (status != DNG_STATUS_OK)
     {
         printf("Destroy failed: %u\n", (unsigned)status);
-        return 8;
+        return 10;
     }
 
     // Negative: window handle is invalid once destroyed.
@@ -146,14 +168,14 @@ int main()
     if (status != DNG_STATUS_INVALID_ARG)
     {
         printf("GetSize after destroy did not fail as expected: %u\n", (unsigned)status);
-        return 9;
+        return 11;
     }
 
     status = dng::WindowDestroy(module_api.window, handle);
     if (status != DNG_STATUS_INVALID_ARG)
     {
         printf("Destroy twice did not fail as expected: %u\n", (unsigned)status);
-        return 10;
+        return 12;
     }
 
     if (module_api.shutdown)
@@ -162,7 +184,7 @@ int main()
         if (status != DNG_STATUS_OK)
         {
             printf("Shutdown failed: %u\n", (unsigned)status);
-            return 11;
+            return 13;
         }
         // Shutdown is single-use for dynamically allocated contexts.
         module_api.window.ctx = NULL;